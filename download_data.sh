@@ -18,5 +18,6 @@ cp crawlit/data/dofus/allweapons.json    data/weapons.json
 cp crawlit/data/dofus/mount.json         data/mounts.json
 cp crawlit/data/dofus/pet.json           data/pets.json
 cp crawlit/data/dofus/set.json           data/sets.json
+cp examples/spells.json                  data/spells.json
 
 rm -rf crawlit
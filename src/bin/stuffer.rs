@@ -3,20 +3,31 @@ extern crate lazy_static;
 extern crate dofus_stuff;
 extern crate rand;
 extern crate regex;
+#[macro_use]
 extern crate serde_json;
 
 use std::collections::HashMap;
+use std::fmt;
 use std::fs::File;
 use std::io;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use regex::Regex;
 
-use dofus_stuff::character::{Character, RawCaracsValue};
+use dofus_stuff::character::{Character, CharacterError, RawCaracsValue};
 use dofus_stuff::dofapi::{
-    fix_all_trophy, CaracKind, Element, Equipement, ItemType, Set,
+    fix_all_trophy, load_spells, CaracKind, CastableSpell, Element,
+    Equipement, ItemType, Set, Spell,
+};
+use dofus_stuff::exact::{solve_additive, ExactConfig};
+use dofus_stuff::search::{
+    optimize_character_parallel, prune_dominated, Allocation, AnnealingConfig,
+    Constraint, Operator, OptimizerConfig, SearchConfig, SearchProgress,
 };
-use dofus_stuff::search::optimize_character;
-use serde::Deserialize;
+use serde::{de, Deserialize, Deserializer};
 
 //   ____                _              _
 //  / ___|___  _ __  ___| |_ __ _ _ __ | |_ ___
@@ -36,6 +47,12 @@ const EQUIPEMENT_FILES: [&str; 4] = [
 /// File containing the list of sets.
 const SET_FILE: &str = "./data/sets.json";
 
+/// File containing the list of spells, which is optional.
+const SPELL_FILE: &str = "./data/spells.json";
+
+/// Minimal duration between two displays of the progress of the search.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
 /// Default file to read as input when no parameter is specified.
 const DEFAULT_INPUT_PATH: &str = "input.json";
 
@@ -57,9 +74,212 @@ pub struct InputRequest {
     #[serde(default)]
     pub banned_types: Vec<ItemType>,
 
-    /// List of approximate expected statistics in the output.
+    /// List of approximate expected statistics in the output, and of hard
+    /// constraints that the output must satisfy.
+    #[serde(default)]
+    pub target: Vec<TargetLine>,
+
+    /// Items that must be part of the output.
+    #[serde(default)]
+    pub pinned: Vec<PinnedItem>,
+
+    /// Number of threads running searches, defaults to the number of CPUs.
+    #[serde(default)]
+    pub threads: Option<usize>,
+
+    /// Number of independent searches, defaults to the number of threads.
+    #[serde(default)]
+    pub restarts: Option<usize>,
+
+    /// Seed of the random generator, picked randomly if not specified.
+    #[serde(default)]
+    pub seed: Option<u64>,
+
+    /// Parameters of the simulated annealing.
+    #[serde(default)]
+    pub annealing: AnnealingConfig,
+
+    /// Optimization strategy, overrides `annealing` if specified.
+    #[serde(default)]
+    pub optimizer: Option<OptimizerConfig>,
+
+    /// Use the exact solver instead of a random search, maximizing a weighted
+    /// sum of characteristics.
+    #[serde(default)]
+    pub exact: Option<ExactConfig>,
+
+    /// Number of alternative builds in the output.
+    #[serde(default = "default_top")]
+    pub top: usize,
+
+    /// Minimal number of different items between two builds of the output.
+    #[serde(default = "default_min_distance")]
+    pub min_distance: usize,
+
+    /// Remove items that are dominated by other items before the search.
+    #[serde(default = "default_prune")]
+    pub prune: bool,
+
+    /// Optimal allocations of characteristic points proposed during the
+    /// search.
     #[serde(default)]
-    pub target: Vec<(RawCaracsValue, f64)>,
+    pub allocation: Allocation,
+}
+
+/// Arguments given through the command line.
+struct Args {
+    input_path: String,
+    threads:    Option<usize>,
+    restarts:   Option<usize>,
+    seed:       Option<u64>,
+    stream:     bool,
+}
+
+impl Args {
+    /// Parse command line arguments, formatted as
+    /// `stuffer [--threads N] [--restarts N] [--seed N] [--stream]
+    /// [input.json]`.
+    fn parse() -> Self {
+        let mut args = std::env::args().skip(1);
+        let mut input_path = None;
+        let mut threads = None;
+        let mut restarts = None;
+        let mut seed = None;
+        let mut stream = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--threads" => threads = Some(parse_number(&arg, args.next())),
+                "--restarts" => {
+                    restarts = Some(parse_number(&arg, args.next()))
+                }
+                "--seed" => seed = Some(parse_number(&arg, args.next())),
+                "--stream" => stream = true,
+                _ => input_path = Some(arg),
+            }
+        }
+
+        Args {
+            input_path: input_path
+                .unwrap_or_else(|| DEFAULT_INPUT_PATH.into()),
+            threads,
+            restarts,
+            seed,
+            stream,
+        }
+    }
+}
+
+/// Either an approximate expected value or a hard constraint, formatted as
+/// `[value, 11]` or `[value, ">=", 11]`.
+pub enum TargetLine {
+    Soft(RawCaracsValue<CastableSpell>, f64),
+    Hard(Constraint<CastableSpell>),
+}
+
+struct TargetLineVisitor;
+
+impl<'de> Deserialize<'de> for TargetLine {
+    fn deserialize<D>(deserializer: D) -> Result<TargetLine, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(TargetLineVisitor)
+    }
+}
+
+impl<'de> de::Visitor<'de> for TargetLineVisitor {
+    type Value = TargetLine;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .write_str(r#"a target line `[value, 11]` or `[value, ">=", 11]`"#)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let value = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+
+        // The second element tells the shape of the line, errors of the
+        // following elements are reported for this shape only
+        let line = match seq.next_element::<serde_json::Value>()? {
+            Some(serde_json::Value::String(op)) => {
+                let op = Operator::deserialize(de::value::StrDeserializer::<
+                    A::Error,
+                >::new(&op))?;
+                let bound = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                TargetLine::Hard(Constraint { value, op, bound })
+            }
+            Some(expected) => {
+                let expected = expected.as_f64().ok_or_else(|| {
+                    de::Error::invalid_type(
+                        de::Unexpected::Other(&expected.to_string()),
+                        &"an expected value or an operator",
+                    )
+                })?;
+                TargetLine::Soft(value, expected)
+            }
+            None => return Err(de::Error::invalid_length(1, &self)),
+        };
+
+        if seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::custom(
+                "trailing elements in a target line",
+            ));
+        }
+
+        Ok(line)
+    }
+}
+
+/// Reference to an item of the database.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum ItemRef {
+    Id(u64),
+    Name(String),
+}
+
+impl ItemRef {
+    fn find<'i>(&self, db: &'i [Equipement]) -> Option<&'i Equipement> {
+        db.iter().find(|item| match self {
+            ItemRef::Id(id) => item._id == *id,
+            ItemRef::Name(name) => item.name == *name,
+        })
+    }
+}
+
+impl fmt::Display for ItemRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemRef::Id(id) => write!(f, "#{}", id),
+            ItemRef::Name(name) => write!(f, "`{}`", name),
+        }
+    }
+}
+
+/// An item that must be equiped, eventually in a specific slot.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum PinnedItem {
+    InSlot { item: ItemRef, slot: usize },
+    Item(ItemRef),
+}
+
+/// Parse the value following a command line flag.
+fn parse_number<T: FromStr>(flag: &str, value: Option<String>) -> T {
+    value
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| {
+            eprintln!("Expected a positive number after `{}`", flag);
+            std::process::exit(1)
+        })
 }
 
 /// Default level of a character.
@@ -67,6 +287,18 @@ fn default_level() -> u8 {
     200
 }
 
+fn default_top() -> usize {
+    1
+}
+
+fn default_min_distance() -> usize {
+    1
+}
+
+fn default_prune() -> bool {
+    true
+}
+
 fn main() -> io::Result<()> {
     // --- Open item database
     eprintln!("-- Loading data...");
@@ -86,6 +318,12 @@ fn main() -> io::Result<()> {
         vec.into_iter().map(|set: Set| (set._id, set)).collect()
     };
 
+    let spells: Vec<Spell> = match load_spells(SPELL_FILE) {
+        Ok(spells) => spells,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => panic!("Could not read `{}`: {}", SPELL_FILE, err),
+    };
+
     let mut equipements: Vec<Equipement> = EQUIPEMENT_FILES
         .iter()
         .map(|path| {
@@ -117,8 +355,9 @@ fn main() -> io::Result<()> {
                     static ref RE_DMG: Regex = Regex::new(r"\(.*\)").unwrap();
                 }
 
-                item.statistics = HashMap::from(item.statistics)
-                    .into_iter()
+                item.statistics = item
+                    .statistics
+                    .iter()
                     .filter(|carac| match carac {
                         (CaracKind::AP, bounds) => {
                             // Weapons AP reduction is strangly formatted and
@@ -132,8 +371,7 @@ fn main() -> io::Result<()> {
                         }
                         _ => true,
                     })
-                    .collect::<HashMap<_, _>>()
-                    .into();
+                    .collect();
             }
 
             item
@@ -143,12 +381,11 @@ fn main() -> io::Result<()> {
     // --- Read output and generate appropriate stuff and character.
     eprintln!("-- Reading input...");
 
-    let input_path = std::env::args()
-        .nth(1)
-        .unwrap_or_else(|| DEFAULT_INPUT_PATH.into());
+    let args = Args::parse();
+    let input_path = &args.input_path;
 
     let input: InputRequest = {
-        let file = File::open(&input_path).unwrap_or_else(|err| {
+        let file = File::open(input_path).unwrap_or_else(|err| {
             panic!("Could not open input file `{}`: {}", input_path, err)
         });
         serde_json::from_reader(io::BufReader::new(file)).unwrap_or_else(
@@ -165,26 +402,281 @@ fn main() -> io::Result<()> {
         .cloned()
         .collect();
 
-    let init_character = Character::new(input.level, &sets);
+    let mut init_character = Character::new(input.level, &sets);
+
+    for pinned in &input.pinned {
+        let (item_ref, slot) = match pinned {
+            PinnedItem::InSlot { item, slot } => (item, Some(*slot)),
+            PinnedItem::Item(item) => (item, None),
+        };
+
+        let item = item_ref.find(&equipements).unwrap_or_else(|| {
+            eprintln!("Could not find pinned item {}", item_ref);
+            std::process::exit(1)
+        });
+
+        if let Err(err) = init_character.pin(item, slot) {
+            eprintln!("Could not pin item {}: {}", item_ref, err);
+            std::process::exit(1)
+        }
+    }
 
-    for target_line in &input.target {
-        if let RawCaracsValue::Carac(CaracKind::Special(ref s)) = target_line.0
-        {
+    let mut target = Vec::new();
+    let mut constraints = Vec::new();
+
+    for line in input.target {
+        let resolved = match line {
+            TargetLine::Soft(value, expected) => value
+                .resolve_spells(&spells)
+                .map(|value| target.push((value, expected))),
+            TargetLine::Hard(constraint) => constraint
+                .resolve_spells(&spells)
+                .map(|constraint| constraints.push(constraint)),
+        };
+
+        if let Err(err) = resolved {
+            eprintln!("Invalid target in the input: {}", err);
+            std::process::exit(1)
+        }
+    }
+
+    let values = target
+        .iter()
+        .map(|(value, _)| value)
+        .chain(constraints.iter().map(|constraint| &constraint.value));
+
+    for value in values {
+        if let RawCaracsValue::Carac(CaracKind::Special(s)) = value {
             eprintln!(r"/!\ Unrecognised target in the input: `{}`", s);
         }
     }
 
+    if let Some(config) = &input.exact {
+        solve_exact(
+            init_character,
+            &target,
+            &constraints,
+            &filtered_equipements,
+            config,
+        );
+        return Ok(());
+    }
+
+    let searched_equipements: Vec<Equipement> = if input.prune {
+        eprintln!("-- Pruning dominated items...");
+        let pruning = prune_dominated(
+            &init_character,
+            &target,
+            &constraints,
+            &filtered_equipements,
+        );
+
+        for (slot, (before, after)) in pruning.pools.iter().enumerate() {
+            if *before > 0 {
+                eprintln!(" - slot #{}: {} -> {} items", slot, before, after);
+            }
+        }
+
+        pruning.items.into_iter().cloned().collect()
+    } else {
+        filtered_equipements
+    };
+
     // --- Build the stuff
     eprintln!("-- Building random stuffs...");
 
-    let character = optimize_character(
+    let threads = args.threads.or(input.threads).unwrap_or_else(|| {
+        thread::available_parallelism().map_or(1, |count| count.get())
+    });
+    let restarts = args.restarts.or(input.restarts).unwrap_or(threads);
+    let seed = args.seed.or(input.seed).unwrap_or_else(rand::random);
+
+    let config = SearchConfig {
+        optimizer: input
+            .optimizer
+            .unwrap_or(OptimizerConfig::Annealing(input.annealing)),
+        threads,
+        restarts,
+        seed,
+        top: input.top,
+        min_distance: input.min_distance,
+        allocation: input.allocation,
+    };
+
+    // Best score found by all chains, and last time progress was displayed
+    let state = Mutex::new((f64::NEG_INFINITY, Instant::now()));
+
+    let observer = |search: &SearchProgress| {
+        if let Some((character, score)) = search.improved {
+            let mut state = state.lock().unwrap();
+
+            if *score > state.0 {
+                state.0 = *score;
+
+                if args.stream {
+                    let mut line = json_build(character, *score, &target);
+                    line["chain"] = json!(search.chain);
+                    line["step"] = json!(search.progress.step);
+                    println!("{}", line);
+                }
+            }
+        }
+
+        if !args.stream && search.progress.step.is_multiple_of(1000) {
+            let mut state = state.lock().unwrap();
+
+            if state.1.elapsed() >= PROGRESS_INTERVAL {
+                state.1 = Instant::now();
+                eprint!(
+                    "\r-- chain {}/{}, step {}/{}, best score: {:.3e}  ",
+                    search.chain + 1,
+                    config.restarts,
+                    search.progress.step,
+                    search.progress.steps,
+                    state.0
+                );
+            }
+        }
+
+        true
+    };
+
+    let builds = optimize_character_parallel(
         init_character,
-        &input.target,
-        &filtered_equipements,
-    );
+        &target,
+        &constraints,
+        &searched_equipements,
+        &config,
+        &observer,
+    )
+    .unwrap_or_else(|err| {
+        if let CharacterError::Infeasible(violated) = &err {
+            eprintln!("Could not satisfy the following hard constraints:");
+
+            for &index in violated {
+                let constraint = &constraints[index];
+                eprintln!(
+                    " - {:?} {} {}",
+                    constraint.value, constraint.op, constraint.bound
+                );
+            }
+        } else {
+            eprintln!("Could not build a stuff: {}", err);
+        }
+
+        std::process::exit(1)
+    });
 
     // --- Show results
+    eprintln!("\n-- Result...");
+
+    if args.stream {
+        // Keep the standard output readable line by line as JSON
+        for (rank, (character, score)) in builds.iter().enumerate() {
+            let mut line = json_build(character, *score, &target);
+            line["rank"] = json!(rank + 1);
+            line["valid"] = json!(character.validate().is_valid());
+            println!("{}", line);
+        }
+
+        println!("{}", json!({ "seed": seed }));
+        return Ok(());
+    }
+
+    for (rank, (character, score)) in builds.iter().enumerate() {
+        if builds.len() > 1 {
+            println!("\n=== Build #{} (score: {:.3e}) ===", rank + 1, score);
+        }
+
+        print_build(character, &target, &constraints);
+    }
+
+    println!("seed: {}", seed);
+    println!("------------------------------------------------");
+
+    Ok(())
+}
+
+/// Run the exact solver and display its build, target lines are only
+/// displayed.
+fn solve_exact<'i>(
+    init: Character<'i>,
+    target: &[(RawCaracsValue, f64)],
+    constraints: &[Constraint],
+    db_equipements: &'i [Equipement],
+    config: &ExactConfig,
+) {
+    if !constraints.is_empty() {
+        eprintln!("Hard constraints are not supported by the exact solver");
+        std::process::exit(1)
+    }
+
+    if config.weights.is_empty() {
+        eprintln!("The exact solver requires weights in `exact.weights`");
+        std::process::exit(1)
+    }
+
+    eprintln!("-- Solving...");
+
+    let solution = solve_additive(init, db_equipements, config)
+        .unwrap_or_else(|err| {
+            eprintln!("Could not build a stuff: {}", err);
+            std::process::exit(1)
+        });
+
     eprintln!("-- Result...");
+    print_build(&solution.character, target, &[]);
+
+    if solution.is_optimal() {
+        println!("score: {} (optimal)", solution.score);
+    } else {
+        println!(
+            "score: {}, upper bound: {} (gap: {:.2}%)",
+            solution.score,
+            solution.bound,
+            100. * solution.gap() / solution.bound.abs().max(1e-9)
+        );
+    }
+
+    if !solution.valid {
+        println!("the build is not valid, so it is not proven optimal");
+    }
+
+    println!("explored nodes: {}", solution.nodes);
+    println!("------------------------------------------------");
+}
+
+/// Items, score and target values of a build, as printed with `--stream`.
+fn json_build(
+    character: &Character,
+    score: f64,
+    target: &[(RawCaracsValue, f64)],
+) -> serde_json::Value {
+    let caracs = character.get_caracs();
+    let items: Vec<_> = character
+        .iter_items()
+        .map(
+            |item| json!({"id": item._id, "name": item.name, "url": item.url}),
+        )
+        .collect();
+    let targets: Vec<_> = target
+        .iter()
+        .map(|(value, _)| json!([format!("{:?}", value), caracs.eval(value)]))
+        .collect();
+
+    json!({
+        "score": score,
+        "items": items,
+        "targets": targets,
+    })
+}
+
+/// Display the items, characteristics and target values of a build.
+fn print_build(
+    character: &Character,
+    target: &[(RawCaracsValue, f64)],
+    constraints: &[Constraint],
+) {
     println!("------------------------------------------------");
     character
         .item_slots
@@ -225,25 +717,28 @@ fn main() -> io::Result<()> {
         println!(" {:35} {:>10}", stat, caracs.get_carac(stat));
     }
     println!("------------------------------------------------");
-    for (target, val) in input.target {
+    for (target, val) in target {
         println!(
             " - {:?}: {:.2} / {}",
             target,
-            character.get_caracs().eval(&target),
+            character.get_caracs().eval(target),
             val
         );
     }
+    for constraint in constraints {
+        println!(
+            " - {:?}: {:.2} {} {}",
+            constraint.value,
+            character.get_caracs().eval(&constraint.value),
+            constraint.op,
+            constraint.bound
+        );
+    }
     println!("------------------------------------------------");
-    println!("\nstats: {:?}", character.base_stats);
-    println!(
-        "conditions ({}): {:?}",
-        character.condition_overflow(&character.all_conditions()),
-        character.all_conditions()
-    );
-    println!(
-        "conflicts: {}",
-        character.condition_overflow(&character.all_conditions())
-    );
-
-    Ok(())
+    // Sort characteristics to keep the output of a given seed reproducible
+    let mut base_stats: Vec<_> = character.base_stats.iter().collect();
+    base_stats.sort_by_key(|(kind, _)| kind.index());
+    println!("\nstats: {:?}", base_stats);
+    println!("------------------------------------------------");
+    print!("{}", character.validate());
 }
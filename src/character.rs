@@ -1,17 +1,22 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::convert::TryInto;
+use std::fmt;
 
 use serde::Deserialize;
 
+use crate::distribution::Distribution;
 use crate::dofapi::{
-    CaracKind, Condition, ConditionAtom, Effect, Element, Equipement,
-    ItemType, Set, SpellEffects,
+    CaracKind, CaracVec, CastableSpell, Condition, ConditionAtom, Effect,
+    Element, Equipement, Influence, ItemType, Set, Spell, SpellEffects,
+    SpellError, SpellLevel, Target, CARAC_KIND_COUNT,
 };
 
 #[derive(Clone, Debug)]
 pub struct ItemSlot<'a, 'i> {
     allowed: &'a [ItemType],
     item:    Option<&'i Equipement>,
+    locked:  bool,
 }
 
 impl<'a, 'i> ItemSlot<'a, 'i> {
@@ -19,18 +24,48 @@ impl<'a, 'i> ItemSlot<'a, 'i> {
         ItemSlot {
             allowed,
             item: None,
+            locked: false,
         }
     }
 
-    pub fn equip(&mut self, item: &'i Equipement) {
+    /// Equip an item in this slot, replacing the previous one. This only
+    /// checks the slot itself, see `Character::equip`.
+    pub(crate) fn equip(
+        &mut self,
+        item: &'i Equipement,
+    ) -> Result<(), CharacterError<'i>> {
+        if self.locked {
+            return Err(CharacterError::LockedSlot);
+        }
+
         if !self.allowed.contains(&item.item_type) {
-            panic!("Trying to equip incorrect item type");
+            return Err(CharacterError::WrongItemType(item));
         }
+
         self.item = Some(item);
+        Ok(())
+    }
+
+    /// Remove the item of this slot.
+    pub fn unequip(&mut self) -> Result<&'i Equipement, CharacterError<'i>> {
+        if self.locked {
+            return Err(CharacterError::LockedSlot);
+        }
+
+        self.item.take().ok_or(CharacterError::EmptySlot)
+    }
+
+    /// Prevent the item of this slot from being changed.
+    pub fn lock(&mut self) {
+        self.locked = true;
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
     }
 
     pub fn get_allowed(&self) -> &'a [ItemType] {
-        &self.allowed
+        self.allowed
     }
 
     pub fn get_item(&self) -> Option<&'i Equipement> {
@@ -49,6 +84,71 @@ impl<'a, 'i> ItemSlot<'a, 'i> {
 pub enum CharacterError<'c> {
     NotEnoughPoints,
     NotEnoughCaracs(&'c CaracKind),
+    WrongItemType(&'c Equipement),
+    LevelTooHigh(&'c Equipement),
+    TwoHandedConflict(&'c Equipement, &'c Equipement),
+    NoFreeSlot(&'c Equipement),
+    InvalidSlot(usize),
+    LockedSlot,
+    EmptySlot,
+    EmptyPool,
+    /// No build satisfies the hard constraints with given indices.
+    Infeasible(Vec<usize>),
+}
+
+impl fmt::Display for CharacterError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CharacterError::NotEnoughPoints => {
+                write!(f, "not enough characteristic points")
+            }
+            CharacterError::NotEnoughCaracs(kind) => {
+                write!(f, "not enough points invested in {}", kind)
+            }
+            CharacterError::WrongItemType(item) => write!(
+                f,
+                "`{}` ({:?}) can't be equiped in this slot",
+                item.name, item.item_type
+            ),
+            CharacterError::LevelTooHigh(item) => write!(
+                f,
+                "`{}` requires level {} which is above the character's level",
+                item.name, item.level
+            ),
+            CharacterError::TwoHandedConflict(item, other) => write!(
+                f,
+                "`{}` can't be equiped together with `{}` as one of them is \
+                 a two-handed weapon and the other a shield",
+                item.name, other.name
+            ),
+            CharacterError::NoFreeSlot(item) => write!(
+                f,
+                "there is no free slot left to equip `{}`",
+                item.name
+            ),
+            CharacterError::InvalidSlot(slot) => {
+                write!(f, "there is no slot with index {}", slot)
+            }
+            CharacterError::LockedSlot => {
+                write!(f, "the item of this slot can't be changed")
+            }
+            CharacterError::EmptySlot => write!(f, "no item in this slot"),
+            CharacterError::EmptyPool => write!(
+                f,
+                "no item can be equiped in any slot, make sure the level and \
+                 banned types of the input are correct"
+            ),
+            CharacterError::Infeasible(constraints) => {
+                write!(f, "no build satisfies the hard constraints")?;
+
+                for index in constraints {
+                    write!(f, " #{}", index)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -57,7 +157,8 @@ pub struct Character<'i> {
     pub base_stats: HashMap<&'i CaracKind, u16>,
     pub unspent:    u16,
     // Contextual attributes
-    sets: &'i HashMap<u64, Set>,
+    level:          u8,
+    sets:           &'i HashMap<u64, Set>,
 }
 
 impl<'i> Character<'i> {
@@ -100,48 +201,209 @@ impl<'i> Character<'i> {
             ],
             base_stats: HashMap::new(),
             unspent: 5 * (u16::from(level) - 1),
+            level,
             sets,
         }
     }
 
+    pub fn get_level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn get_sets(&self) -> &'i HashMap<u64, Set> {
+        self.sets
+    }
+
+    /// Equip an item in a slot, checking that the character is allowed to
+    /// wear it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::*;
+    /// # use dofus_stuff::dofapi::Equipement;
+    /// # let parse_item = |item_type, level| -> Equipement {
+    /// #     serde_json::from_str(&format!(
+    /// #         r#"{{
+    /// #             "type": "{}", "ankamaId": {}, "_id": {}, "name": "Item",
+    /// #             "level": {}, "url": "", "imgUrl": ""
+    /// #         }}"#,
+    /// #         item_type, level, level, level
+    /// #     ))
+    /// #     .unwrap()
+    /// # };
+    /// let hat = parse_item("Hat", 100);
+    /// let big_hat = parse_item("Hat", 200);
+    /// let bow = parse_item("Bow", 101);
+    /// let shield = parse_item("Shield", 102);
+    ///
+    /// let db_sets = HashMap::new();
+    /// let mut character = Character::new(150, &db_sets);
+    ///
+    /// // The first slot holds hats, the second one holds cloaks
+    /// assert_eq!(character.equip(0, &hat), Ok(()));
+    /// assert_eq!(
+    ///     character.equip(1, &hat),
+    ///     Err(CharacterError::WrongItemType(&hat))
+    /// );
+    /// assert_eq!(
+    ///     character.equip(99, &hat),
+    ///     Err(CharacterError::InvalidSlot(99))
+    /// );
+    /// assert_eq!(
+    ///     character.equip(0, &big_hat),
+    ///     Err(CharacterError::LevelTooHigh(&big_hat))
+    /// );
+    ///
+    /// // A two-handed weapon leaves no room for a shield
+    /// assert_eq!(character.equip(7, &bow), Ok(()));
+    /// assert_eq!(
+    ///     character.equip(9, &shield),
+    ///     Err(CharacterError::TwoHandedConflict(&shield, &bow))
+    /// );
+    ///
+    /// assert_eq!(character.unequip(7), Ok(&bow));
+    /// assert_eq!(character.unequip(7), Err(CharacterError::EmptySlot));
+    /// assert_eq!(character.equip(9, &shield), Ok(()));
+    /// ```
+    pub fn equip(
+        &mut self,
+        slot: usize,
+        item: &'i Equipement,
+    ) -> Result<(), CharacterError<'i>> {
+        if slot >= self.item_slots.len() {
+            return Err(CharacterError::InvalidSlot(slot));
+        }
+
+        if item.level > self.level {
+            return Err(CharacterError::LevelTooHigh(item));
+        }
+
+        let conflict = |other: &Equipement| {
+            (item.is_two_handed() && other.item_type == ItemType::Shield)
+                || (item.item_type == ItemType::Shield
+                    && other.is_two_handed())
+        };
+
+        if let Some(other) = self
+            .item_slots
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != slot)
+            .filter_map(|(_, other_slot)| other_slot.item)
+            .find(|other| conflict(other))
+        {
+            return Err(CharacterError::TwoHandedConflict(item, other));
+        }
+
+        self.item_slots[slot].equip(item)
+    }
+
+    /// Remove the item equiped in a slot.
+    pub fn unequip(
+        &mut self,
+        slot: usize,
+    ) -> Result<&'i Equipement, CharacterError<'i>> {
+        self.item_slots
+            .get_mut(slot)
+            .ok_or(CharacterError::InvalidSlot(slot))?
+            .unequip()
+    }
+
+    /// Equip an item and lock its slot so that it is never replaced. If no
+    /// slot is specified, the first available slot for this kind of item is
+    /// used.
+    ///
+    /// Returns the slot the item was equiped in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::*;
+    /// # use dofus_stuff::dofapi::Equipement;
+    /// # let ring: Equipement = serde_json::from_str(
+    /// #     r#"{
+    /// #         "type": "Ring", "ankamaId": 1, "_id": 1, "name": "Ring",
+    /// #         "level": 1, "url": "", "imgUrl": ""
+    /// #     }"#,
+    /// # )
+    /// # .unwrap();
+    ///
+    /// let db_sets = HashMap::new();
+    /// let mut character = Character::new(200, &db_sets);
+    ///
+    /// // Rings go in slots 3 and 4
+    /// assert_eq!(character.pin(&ring, None), Ok(3));
+    /// assert_eq!(character.pin(&ring, None), Ok(4));
+    /// assert_eq!(
+    ///     character.pin(&ring, None),
+    ///     Err(CharacterError::NoFreeSlot(&ring))
+    /// );
+    /// assert_eq!(
+    ///     character.pin(&ring, Some(42)),
+    ///     Err(CharacterError::InvalidSlot(42))
+    /// );
+    ///
+    /// // Pinned slots can't be emptied nor replaced
+    /// assert_eq!(character.unequip(3), Err(CharacterError::LockedSlot));
+    /// assert_eq!(character.equip(4, &ring), Err(CharacterError::LockedSlot));
+    /// ```
+    pub fn pin(
+        &mut self,
+        item: &'i Equipement,
+        slot: Option<usize>,
+    ) -> Result<usize, CharacterError<'i>> {
+        let slot = match slot {
+            Some(slot) if slot >= self.item_slots.len() => {
+                return Err(CharacterError::InvalidSlot(slot));
+            }
+            Some(slot) => slot,
+            None => self
+                .item_slots
+                .iter()
+                .position(|slot| {
+                    !slot.is_locked()
+                        && slot.get_allowed().contains(&item.item_type)
+                })
+                .ok_or(CharacterError::NoFreeSlot(item))?,
+        };
+
+        self.equip(slot, item)?;
+        self.item_slots[slot].lock();
+        Ok(slot)
+    }
+
     /// Iterator over items currently equiped.
-    pub fn iter_items(&self) -> impl Iterator<Item = &Equipement> {
+    pub fn iter_items(&self) -> impl Iterator<Item = &'i Equipement> + '_ {
         self.item_slots.iter().filter_map(|slot| slot.item)
     }
 
     pub fn get_caracs(&self) -> RawCaracs {
-        let items_vals = self
-            .iter_items()
-            .map(|item| {
-                item.statistics.as_map().iter().map(|(kind, bounds)| {
-                    (kind, *std::cmp::max(bounds.start(), bounds.end()))
-                })
-            })
-            .flatten();
+        let mut ret = CaracVec::default();
 
-        let sets_vals = self
-            .iter_set_synergies()
-            .filter_map(|(set_id, count)| {
-                Some(
-                    self.sets.get(&set_id)?.bonus.get(&count)?.as_map().iter(),
-                )
-            })
-            .flatten()
-            .map(|(kind, bounds)| {
-                (kind, *std::cmp::max(bounds.start(), bounds.end()))
-            });
+        for (kind, &val) in &self.base_stats {
+            let val: i16 = val.try_into().expect("Base statistic overflow");
+            ret.add(kind, val);
+        }
 
-        let base_vals = self.base_stats.iter().map(|(&kind, val)| {
-            let val: i16 =
-                val.clone().try_into().expect("Base statistic overflow");
-            (kind, val)
-        });
+        for item in self.iter_items() {
+            ret += item.statistics.values();
+        }
+
+        for (set_id, count) in self.iter_set_synergies() {
+            if let Some(bonus) =
+                self.sets.get(&set_id).and_then(|set| set.bonus.get(&count))
+            {
+                ret += bonus.values();
+            }
+        }
 
-        let mut ret = HashMap::new();
-        for (kind, val) in base_vals.chain(items_vals).chain(sets_vals) {
-            ret.entry(kind).and_modify(|x| *x += val).or_insert(val);
+        RawCaracs {
+            caracs: ret,
+            level:  self.level,
         }
-        RawCaracs(ret)
     }
 
     pub fn iter_set_synergies(&self) -> impl Iterator<Item = (u64, u8)> {
@@ -194,7 +456,7 @@ impl<'i> Character<'i> {
                 (q + 1) * (50 * q + r)
             }
             other => {
-                panic!(format!("Impossible to spend points for `{:?}`", other))
+                panic!("Impossible to spend points for `{:?}`", other)
             }
         }
     }
@@ -275,7 +537,7 @@ impl<'i> Character<'i> {
         &mut self,
         kind: &'i CaracKind,
         amount: u16,
-    ) -> Result<(), CharacterError> {
+    ) -> Result<(), CharacterError<'i>> {
         let cost = self.carac_spend_cost(kind, amount);
 
         if cost > self.unspent {
@@ -311,7 +573,7 @@ impl<'i> Character<'i> {
         &mut self,
         kind: &'i CaracKind,
         amount: u16,
-    ) -> Result<(), CharacterError> {
+    ) -> Result<(), CharacterError<'i>> {
         let recovered = self.carac_unspend_recover(kind, amount)?;
         self.unspent += recovered;
 
@@ -367,7 +629,7 @@ impl<'i> Character<'i> {
         kind: &'i CaracKind,
         amount: u16,
         seek_from: &'i CaracKind,
-    ) -> Result<(), CharacterError> {
+    ) -> Result<(), CharacterError<'i>> {
         let cost = self.carac_spend_cost(kind, amount);
 
         // If there is not enough unspent points, seek `cost - self.unspent`
@@ -431,43 +693,63 @@ impl<'i> Character<'i> {
         // a cache mechanic.
         let caracs = self.get_caracs();
 
-        let atom_overflow = |atom: &ConditionAtom| match atom {
+        cond.clauses()
+            .iter()
+            .map(|clause| {
+                clause
+                    .iter()
+                    .map(|atom| self.atom_overflow(&caracs, atom))
+                    .min_by(|a, b| a.partial_cmp(b).unwrap())
+                    .expect("Empty clause are not allowed")
+            })
+            .sum()
+    }
+
+    /// Check if a single atom of a condition is satisfied.
+    fn is_atom_met(&self, caracs: &RawCaracs, atom: &ConditionAtom) -> bool {
+        match atom {
+            ConditionAtom::Stats(kind, order, target) => {
+                caracs.get_carac(kind).cmp(target) == *order
+            }
+            ConditionAtom::RestrictSetBonuses => self.count_set_bonuses() <= 2,
+            ConditionAtom::Other(_) => true,
+        }
+    }
+
+    /// Compute an approximate smithmage weight value required to complie to a
+    /// single atom of a condition, zero if it is satisfied.
+    fn atom_overflow(&self, caracs: &RawCaracs, atom: &ConditionAtom) -> f64 {
+        match atom {
             ConditionAtom::Stats(kind, order, target) => {
                 let current = caracs.get_carac(kind);
 
                 if current.cmp(target) != *order {
+                    let gap = i32::from(current) - i32::from(*target);
                     kind.smithmage_weight().unwrap_or(0.)
-                        * f64::from((current - target).abs() + 1)
+                        * f64::from(gap.abs() + 1)
                 } else {
                     0.
                 }
             }
             ConditionAtom::RestrictSetBonuses => {
-                let count_synergies: u8 = self
-                    .iter_set_synergies()
-                    .map(|(_, count)| count)
-                    .filter(|&count| count > 1)
-                    .sum();
+                let count_set_bonuses = self.count_set_bonuses();
 
-                if count_synergies > 2 {
+                if count_set_bonuses > 2 {
                     CaracKind::AP.smithmage_weight().unwrap()
-                        * f64::from(count_synergies - 2)
+                        * f64::from(count_set_bonuses - 2)
                 } else {
                     0.
                 }
             }
             ConditionAtom::Other(_) => 0.,
-        };
+        }
+    }
 
-        cond.clauses()
-            .iter()
-            .map(|clause| {
-                clause
-                    .iter()
-                    .map(|atom| atom_overflow(atom))
-                    .min_by(|a, b| a.partial_cmp(b).unwrap())
-                    .expect("Empty clause are not allowed")
-            })
+    /// Count the number of items that take part in an active set bonus.
+    pub fn count_set_bonuses(&self) -> u8 {
+        self.iter_set_synergies()
+            .map(|(_, count)| count)
+            .filter(|&count| count > 1)
             .sum()
     }
 
@@ -480,11 +762,7 @@ impl<'i> Character<'i> {
                 // Read second item
                 for item2 in &self.item_slots[(i + 1)..self.item_slots.len()] {
                     if let Some(item2) = item2.item {
-                        if item1._id == item2._id
-                            && (item1.set_id.is_some()
-                                || item1.item_type == ItemType::Trophy
-                                || item1.item_type == ItemType::Dofus)
-                        {
+                        if item1._id == item2._id && item1.is_unique() {
                             conflicts += 1;
                         }
                     }
@@ -494,6 +772,244 @@ impl<'i> Character<'i> {
 
         conflicts
     }
+
+    /// Number of items of this character that are not worn by another one,
+    /// independently of the slots they are equiped in.
+    pub fn count_different_items(&self, other: &Character) -> usize {
+        let sorted_ids = |character: &Character| {
+            let mut ids: Vec<_> =
+                character.iter_items().map(|item| item._id).collect();
+            ids.sort_unstable();
+            ids
+        };
+
+        let ids = sorted_ids(self);
+        let other_ids = sorted_ids(other);
+        let (mut i, mut j, mut common) = (0, 0, 0);
+
+        while i < ids.len() && j < other_ids.len() {
+            match ids[i].cmp(&other_ids[j]) {
+                Ordering::Less => i += 1,
+                Ordering::Greater => j += 1,
+                Ordering::Equal => {
+                    common += 1;
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+
+        std::cmp::max(ids.len(), other_ids.len()) - common
+    }
+
+    /// Check that the character could actually be built in game, and explain
+    /// why it couldn't.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::*;
+    /// use dofus_stuff::dofapi::{CaracKind::*, Element::*, Equipement};
+    ///
+    /// let db_sets = HashMap::new();
+    /// let mut character = Character::new(100, &db_sets);
+    /// assert!(!character.validate().is_valid());
+    ///
+    /// character.carac_spend(&Vitality, 495).unwrap();
+    /// assert!(character.validate().is_valid());
+    ///
+    /// let hat: Equipement = serde_json::from_str(
+    ///     r#"{
+    ///         "type": "Hat", "ankamaId": 1, "_id": 1, "name": "Hat",
+    ///         "level": 100, "url": "", "imgUrl": "",
+    ///         "conditions": ["Agility > 200", "Emote > 0"]
+    ///     }"#,
+    /// )
+    /// .unwrap();
+    /// character.equip(0, &hat).unwrap();
+    ///
+    /// // Conditions on characteristics without a smithmage weight are
+    /// // reported as well
+    /// let report = character.validate();
+    /// assert_eq!(report.issues.len(), 2);
+    /// assert!(report
+    ///     .issues
+    ///     .iter()
+    ///     .all(|issue| matches!(issue, ValidationIssue::UnmetCondition(_))));
+    /// ```
+    pub fn validate(&self) -> ValidationReport<'i> {
+        let caracs = self.get_caracs();
+        let mut issues = Vec::new();
+
+        // Conditions of items
+        for clause in self.all_conditions().into_clauses() {
+            let is_unmet =
+                clause.iter().all(|atom| !self.is_atom_met(&caracs, atom));
+
+            if is_unmet {
+                issues.push(ValidationIssue::UnmetCondition(
+                    clause
+                        .into_iter()
+                        .map(|atom| match atom {
+                            ConditionAtom::Stats(kind, order, required) => {
+                                UnmetAtom::Stats {
+                                    current: caracs.get_carac(&kind),
+                                    kind,
+                                    order,
+                                    required,
+                                }
+                            }
+                            ConditionAtom::RestrictSetBonuses => {
+                                UnmetAtom::RestrictSetBonuses {
+                                    count: self.count_set_bonuses(),
+                                }
+                            }
+                            ConditionAtom::Other(_) => unreachable!(),
+                        })
+                        .collect(),
+                ));
+            }
+        }
+
+        // Unique items equiped several times
+        let mut seen: Vec<(&Equipement, u8)> = Vec::new();
+
+        for item in self.iter_items().filter(|item| item.is_unique()) {
+            match seen.iter_mut().find(|(other, _)| other._id == item._id) {
+                Some((_, count)) => *count += 1,
+                None => seen.push((item, 1)),
+            }
+        }
+
+        for (item, count) in seen.into_iter().filter(|(_, count)| *count > 1) {
+            issues.push(ValidationIssue::DuplicatedItem { item, count });
+        }
+
+        // Items level
+        for item in self.iter_items().filter(|item| item.level > self.level) {
+            issues.push(ValidationIssue::ItemAboveLevel(item));
+        }
+
+        // Characteristic points
+        if self.unspent > 0 {
+            issues.push(ValidationIssue::UnspentPoints(self.unspent));
+        }
+
+        ValidationReport {
+            level: self.level,
+            issues,
+        }
+    }
+}
+
+/// An atom of a condition that is not satisfied by a character.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UnmetAtom {
+    Stats {
+        kind:     CaracKind,
+        order:    Ordering,
+        required: i16,
+        current:  i16,
+    },
+    RestrictSetBonuses {
+        count: u8,
+    },
+}
+
+impl fmt::Display for UnmetAtom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnmetAtom::Stats {
+                kind,
+                order,
+                required,
+                current,
+            } => {
+                let sign = match order {
+                    Ordering::Less => "<",
+                    Ordering::Equal => "=",
+                    Ordering::Greater => ">",
+                };
+                write!(
+                    f,
+                    "{} {} {} (current: {})",
+                    kind, sign, required, current
+                )
+            }
+            UnmetAtom::RestrictSetBonuses { count } => write!(
+                f,
+                "at most 2 items in active set bonuses ({} currently)",
+                count
+            ),
+        }
+    }
+}
+
+/// A reason for which a character can't be built in game.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ValidationIssue<'i> {
+    /// A clause of the conditions of equiped items where no atom is
+    /// satisfied.
+    UnmetCondition(Vec<UnmetAtom>),
+    /// A unique item (Dofus, trophy or set item) equiped several times.
+    DuplicatedItem { item: &'i Equipement, count: u8 },
+    /// An item with a level greater than the character.
+    ItemAboveLevel(&'i Equipement),
+    /// Characteristic points that were not invested.
+    UnspentPoints(u16),
+}
+
+/// List of issues found when validating a character.
+#[derive(Clone, Debug)]
+pub struct ValidationReport<'i> {
+    pub level:  u8,
+    pub issues: Vec<ValidationIssue<'i>>,
+}
+
+impl ValidationReport<'_> {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_valid() {
+            return writeln!(f, " The stuff is valid");
+        }
+
+        for issue in &self.issues {
+            match issue {
+                ValidationIssue::UnmetCondition(atoms) => {
+                    let atoms: Vec<_> =
+                        atoms.iter().map(|atom| atom.to_string()).collect();
+                    writeln!(
+                        f,
+                        " - condition not met: {}",
+                        atoms.join(" or ")
+                    )?;
+                }
+                ValidationIssue::DuplicatedItem { item, count } => writeln!(
+                    f,
+                    " - `{}` is equiped {} times but is unique",
+                    item.name, count
+                )?,
+                ValidationIssue::ItemAboveLevel(item) => writeln!(
+                    f,
+                    " - `{}` requires level {} (character is level {})",
+                    item.name, item.level, self.level
+                )?,
+                ValidationIssue::UnspentPoints(points) => writeln!(
+                    f,
+                    " - {} characteristic points are not spent",
+                    points
+                )?,
+            }
+        }
+
+        Ok(())
+    }
 }
 
 //  ____                 ____
@@ -503,13 +1019,18 @@ impl<'i> Character<'i> {
 // |_| \_\__,_| \_/\_/  \____\__,_|_|  \__,_|\___|___/
 //
 
-pub struct RawCaracs<'c>(HashMap<&'c CaracKind, i16>);
+pub struct RawCaracs {
+    caracs: CaracVec,
+    level:  u8,
+}
 
-impl RawCaracs<'_> {
-    fn as_map(&self) -> &HashMap<&CaracKind, i16> {
-        match self {
-            RawCaracs(map) => map,
-        }
+impl RawCaracs {
+    fn as_vec(&self) -> &CaracVec {
+        &self.caracs
+    }
+
+    pub fn get_level(&self) -> u8 {
+        self.level
     }
 
     pub fn get_base_carac(&self, kind: &CaracKind) -> i16 {
@@ -527,7 +1048,7 @@ impl RawCaracs<'_> {
     }
 
     fn get_raw_carac(&self, kind: &CaracKind) -> i16 {
-        *self.as_map().get(kind).unwrap_or(&0)
+        self.as_vec().get(kind)
     }
 
     pub fn get_carac(&self, kind: &CaracKind) -> i16 {
@@ -557,6 +1078,41 @@ impl RawCaracs<'_> {
         }
     }
 
+    /// Raw characteristics that are summed into `get_carac(kind)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dofus_stuff::character::RawCaracs;
+    /// use dofus_stuff::dofapi::{CaracKind::*, Element::*};
+    ///
+    /// assert_eq!(RawCaracs::carac_sources(&AP), [AP]);
+    /// assert_eq!(RawCaracs::carac_sources(&Dodge), [Dodge, Stats(Air)]);
+    /// ```
+    pub fn carac_sources(kind: &CaracKind) -> Vec<CaracKind> {
+        use CaracKind::*;
+
+        let mut sources = vec![kind.clone()];
+
+        match kind {
+            Initiative => sources.extend(
+                [Element::Air, Element::Earth, Element::Fire, Element::Water]
+                    .iter()
+                    .map(|&elem| Stats(elem)),
+            ),
+            Damage(_) => sources.push(RawDamage),
+            Prospecting => sources.push(Stats(Element::Water)),
+            Pods => sources.push(Stats(Element::Earth)),
+            Dodge | Lock => sources.push(Stats(Element::Air)),
+            APReduction | APResistance | MPReduction | MPResistance => {
+                sources.push(Wisdom)
+            }
+            _ => {}
+        }
+
+        sources
+    }
+
     pub fn eval(&self, value: &RawCaracsValue) -> f64 {
         match value {
             RawCaracsValue::Carac(carac) => self.get_carac(carac) as f64,
@@ -564,9 +1120,24 @@ impl RawCaracs<'_> {
                 self.get_carac(&CaracKind::Stats(elem.effective_stat())) as f64
                     + self.get_carac(&CaracKind::Power) as f64
             }
+            RawCaracsValue::TrapPowStats(elem) => {
+                self.get_carac(&CaracKind::Stats(elem.effective_stat())) as f64
+                    + self.get_carac(&CaracKind::TrapPower) as f64
+            }
             RawCaracsValue::MeanExtraDamage(elem) => {
                 self.mean_extra_damage(*elem)
             }
+            RawCaracsValue::PushbackDamage(cells) => {
+                self.pushback_damage(*cells, &Target::default())
+            }
+            RawCaracsValue::APRemoval(removal) => {
+                self.removal_chance(&CaracKind::APReduction, removal)
+            }
+            RawCaracsValue::MPRemoval(removal) => {
+                self.removal_chance(&CaracKind::MPReduction, removal)
+            }
+            RawCaracsValue::Escape(lock) => self.escape_chance(*lock),
+            RawCaracsValue::Tackle(dodge) => self.tackle_chance(*dodge),
             RawCaracsValue::PerResVariance => {
                 let kinds = [
                     CaracKind::PerResistance(Element::Air),
@@ -583,9 +1154,66 @@ impl RawCaracs<'_> {
                 (square_diffs.sum::<f64>() / 5.).sqrt()
             }
             RawCaracsValue::Resiliance => self.resiliance(),
+            RawCaracsValue::EffectiveHealth(profile) => {
+                self.effective_health(profile)
+            }
             RawCaracsValue::MeanDamage(effects) => {
                 self.mean_weapon_damage(effects)
             }
+            RawCaracsValue::SpellDamage(spell) => {
+                self.mean_weapon_damage(&spell.effects)
+            }
+            RawCaracsValue::TurnDamage(spells) => self.turn_damage(spells),
+            RawCaracsValue::DamagePercentile { spell, percentile } => self
+                .damage_distribution(&spell.effects)
+                .percentile(*percentile),
+            RawCaracsValue::DamageChance { spell, threshold } => {
+                100. * self
+                    .damage_distribution(&spell.effects)
+                    .chance_at_least(*threshold)
+            }
+            RawCaracsValue::TurnDamagePercentile { spells, percentile } => {
+                self.turn_damage_distribution(spells)
+                    .percentile(*percentile)
+            }
+            RawCaracsValue::TurnDamageChance { spells, threshold } => {
+                100. * self
+                    .turn_damage_distribution(spells)
+                    .chance_at_least(*threshold)
+            }
+            RawCaracsValue::TargetDamage { spell, target } => {
+                self.mean_damage_against(&spell.effects, target)
+            }
+            RawCaracsValue::MeanHeal(spell) => self.mean_heal(&spell.effects),
+            RawCaracsValue::MeanShield(spell) => {
+                self.mean_shield(&spell.effects)
+            }
+            RawCaracsValue::Min(values) => values
+                .iter()
+                .map(|value| self.eval(value))
+                .reduce(f64::min)
+                .unwrap_or(0.),
+            RawCaracsValue::Max(values) => values
+                .iter()
+                .map(|value| self.eval(value))
+                .reduce(f64::max)
+                .unwrap_or(0.),
+            RawCaracsValue::Sum(values) => {
+                values.iter().map(|value| self.eval(value)).sum()
+            }
+            RawCaracsValue::WeightedSum(values) => values
+                .iter()
+                .map(|(value, weight)| weight * self.eval(value))
+                .sum(),
+            RawCaracsValue::Ratio(num, den) => {
+                let den = self.eval(den);
+
+                if den == 0. {
+                    0.
+                } else {
+                    self.eval(num) / den
+                }
+            }
         }
     }
 
@@ -611,21 +1239,226 @@ impl RawCaracs<'_> {
         vitality / (1. - mean_res / 100.)
     }
 
-    pub fn mean_extra_damage(&self, element: Element) -> f64 {
-        let base_dmg: f64 = self.get_carac(&CaracKind::Damage(element)).into();
-        let critical: f64 = (10 + self.get_carac(&CaracKind::Critical)).into();
-        let crit_dmg: f64 = self.get_carac(&CaracKind::CriticalDamage).into();
-        base_dmg + (crit_dmg * critical / 100.)
-    }
+    /// Health points needed to absorb the damages described by an incoming
+    /// damage profile, taking percent, fixed and melee/ranged resistances into
+    /// account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::*;
+    /// use dofus_stuff::dofapi::Equipement;
+    ///
+    /// let hat: Equipement = serde_json::from_str(
+    ///     r#"{
+    ///         "type": "Hat", "ankamaId": 1, "_id": 1, "name": "Hat",
+    ///         "level": 150, "url": "", "imgUrl": "",
+    ///         "statistics": [
+    ///             {"% Fire Resistance": {"min": 20, "max": 20}},
+    ///             {"Fire Resistance": {"min": 50, "max": 50}},
+    ///             {"% Melee Resistance": {"min": 10, "max": 10}}
+    ///         ]
+    ///     }"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let db_sets = HashMap::new();
+    /// let mut character = Character::new(200, &db_sets);
+    /// character.equip(0, &hat).unwrap();
+    /// let caracs = character.get_caracs();
+    ///
+    /// let fire_melee: DamageProfile = serde_json::from_str(
+    ///     r#"{"elements": {"Fire": 1}, "hit": 250, "melee": 1}"#,
+    /// )
+    /// .unwrap();
+    /// let ranged: DamageProfile =
+    ///     serde_json::from_str(r#"{"melee": 0}"#).unwrap();
+    ///
+    /// let health = caracs.effective_health(&fire_melee);
+    /// assert!((health - 1150. / 0.576).abs() < 1e-6);
+    /// let health = caracs.effective_health(&ranged);
+    /// assert!((health - 1150. / 0.96).abs() < 1e-6);
+    /// ```
+    pub fn effective_health(&self, profile: &DamageProfile) -> f64 {
+        let vitality = self.get_carac(&CaracKind::Vitality) as f64;
 
-    pub fn mean_effect_damage(
-        &self,
-        effect: &Effect,
-        is_crit: bool,
-        is_spell: bool,
-        is_dist: bool,
-    ) -> f64 {
-        match effect {
+        let range_factor = |kind| 1. - self.get_carac(kind) as f64 / 100.;
+        let range_ratio = profile.melee
+            * range_factor(&CaracKind::PerMeleeResistance)
+            + (1. - profile.melee)
+                * range_factor(&CaracKind::PerRangedResistance);
+
+        let element_ratio = |element| {
+            let per_res = self.get_carac(&CaracKind::PerResistance(element));
+            let fixed_ratio = match profile.hit {
+                Some(hit) if hit > 0. => {
+                    let res = self.get_carac(&CaracKind::Resistance(element));
+                    (hit - res as f64).max(0.) / hit
+                }
+                _ => 1.,
+            };
+
+            fixed_ratio * (1. - per_res as f64 / 100.)
+        };
+
+        let (total_weight, ratio) = Element::ALL
+            .iter()
+            .map(|&element| (profile.get_weight(element), element))
+            .filter(|(weight, _)| *weight > 0.)
+            .fold((0., 0.), |(total, ratio), (weight, element)| {
+                (total + weight, ratio + weight * element_ratio(element))
+            });
+
+        if total_weight <= 0. {
+            return vitality;
+        }
+
+        // Damages can't be fully absorbed, at least 1% of them go through
+        let ratio = (range_ratio * ratio / total_weight).max(0.01);
+        vitality / ratio
+    }
+
+    /// Chance in percent to remove all the AP or MP of an attempt, `kind` is
+    /// either `APReduction` or `MPReduction`.
+    ///
+    /// Each point is removed with probability `R / (2 * E) * remaining /
+    /// total`, bounded between 10% and 90%, where `R` is the reduction of
+    /// the caster and `E` the resistance of the opponent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::*;
+    /// use dofus_stuff::dofapi::CaracKind::*;
+    ///
+    /// let db_sets = HashMap::new();
+    /// let caracs = Character::new(200, &db_sets).get_caracs();
+    /// let chance = |points, resistance| {
+    ///     let removal = Removal {
+    ///         points,
+    ///         resistance,
+    ///         total: Some(6),
+    ///     };
+    ///     caracs.removal_chance(&MPReduction, &removal)
+    /// };
+    ///
+    /// assert_eq!(caracs.get_carac(&MPReduction), 10);
+    /// assert!((chance(1, 10) - 50.).abs() < 1e-9);
+    /// assert!((chance(1, 1000) - 10.).abs() < 1e-9);
+    /// assert!((chance(2, 10) - 20.8333).abs() < 1e-3);
+    /// ```
+    pub fn removal_chance(&self, kind: &CaracKind, removal: &Removal) -> f64 {
+        let reduction = f64::from(self.get_carac(kind));
+        let resistance = f64::from(std::cmp::max(removal.resistance, 1));
+        let total = removal.total.unwrap_or(match kind {
+            CaracKind::APReduction => 12,
+            _ => 6,
+        });
+
+        let chance: f64 = (0..removal.points)
+            .map(|removed| {
+                let remaining = f64::from(total.saturating_sub(removed));
+                let ratio = remaining / f64::from(std::cmp::max(total, 1));
+                (reduction / (2. * resistance) * ratio).clamp(0.1, 0.9)
+            })
+            .product();
+
+        100. * chance
+    }
+
+    /// Percent of AP and MP kept when leaving an opponent with given lock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::*;
+    ///
+    /// # use dofus_stuff::dofapi::Equipement;
+    /// # let hat: Equipement = serde_json::from_str(
+    /// #     r#"{
+    /// #         "type": "Hat", "ankamaId": 1, "_id": 1, "name": "Hat",
+    /// #         "level": 1, "url": "", "imgUrl": "",
+    /// #         "statistics": [{"Dodge": {"min": -2, "max": -2}}]
+    /// #     }"#,
+    /// # )
+    /// # .unwrap();
+    ///
+    /// let db_sets = HashMap::new();
+    /// let mut character = Character::new(200, &db_sets);
+    ///
+    /// let caracs = character.get_caracs();
+    /// assert_eq!(caracs.escape_chance(0), 50.);
+    /// assert_eq!(caracs.escape_chance(-1), 100.);
+    /// assert_eq!(caracs.escape_chance(-2), 100.);
+    /// assert!(caracs.escape_chance(i16::MAX) < 0.01);
+    ///
+    /// // The hat gives -2 dodge
+    /// character.equip(0, &hat).unwrap();
+    /// assert_eq!(character.get_caracs().escape_chance(-2), 0.);
+    /// ```
+    pub fn escape_chance(&self, lock: i16) -> f64 {
+        let dodge = f64::from(self.get_carac(&CaracKind::Dodge));
+        let lock = f64::from(std::cmp::max(i32::from(lock) + 2, 1));
+        100. * ((dodge + 2.) / (2. * lock)).clamp(0., 1.)
+    }
+
+    /// Percent of AP and MP lost by an opponent with given dodge when leaving
+    /// this character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::*;
+    ///
+    /// # use dofus_stuff::dofapi::Equipement;
+    /// # let hat: Equipement = serde_json::from_str(
+    /// #     r#"{
+    /// #         "type": "Hat", "ankamaId": 1, "_id": 1, "name": "Hat",
+    /// #         "level": 1, "url": "", "imgUrl": "",
+    /// #         "statistics": [{"Lock": {"min": -2, "max": -2}}]
+    /// #     }"#,
+    /// # )
+    /// # .unwrap();
+    ///
+    /// let db_sets = HashMap::new();
+    /// let mut character = Character::new(200, &db_sets);
+    ///
+    /// let caracs = character.get_caracs();
+    /// assert_eq!(caracs.tackle_chance(0), 50.);
+    /// assert_eq!(caracs.tackle_chance(100), 0.);
+    ///
+    /// // The hat gives -2 lock
+    /// character.equip(0, &hat).unwrap();
+    /// assert_eq!(character.get_caracs().tackle_chance(-2), 100.);
+    /// ```
+    pub fn tackle_chance(&self, dodge: i16) -> f64 {
+        let lock = self.get_carac(&CaracKind::Lock);
+        let lock = f64::from(std::cmp::max(i32::from(lock) + 2, 1));
+        let dodge = f64::from(dodge);
+        100. * (1. - ((dodge + 2.) / (2. * lock)).clamp(0., 1.))
+    }
+
+    pub fn mean_extra_damage(&self, element: Element) -> f64 {
+        let base_dmg: f64 = self.get_carac(&CaracKind::Damage(element)).into();
+        let critical: f64 = (10 + self.get_carac(&CaracKind::Critical)).into();
+        let crit_dmg: f64 = self.get_carac(&CaracKind::CriticalDamage).into();
+        base_dmg + (crit_dmg * critical / 100.)
+    }
+
+    /// Average damages of an effect against a target.
+    pub fn mean_effect_damage(
+        &self,
+        effect: &Effect,
+        is_crit: bool,
+        is_spell: bool,
+        is_dist: bool,
+        target: &Target,
+    ) -> f64 {
+        match effect {
             Effect::Hit {
                 element, bounds, ..
             } => {
@@ -642,63 +1475,486 @@ impl RawCaracs<'_> {
                         f64::from(self.get_carac(&CaracKind::CriticalDamage));
                 }
 
-                if is_spell {
-                    damage *= 1.
-                        + f64::from(
-                            self.get_carac(&CaracKind::PerSpellDamage),
-                        ) / 100.;
-                } else {
-                    damage *= 1.
-                        + f64::from(
-                            self.get_carac(&CaracKind::PerWeaponDamage),
-                        ) / 100.;
-                }
+                self.apply_resistances(
+                    damage, *element, is_crit, is_dist, target,
+                ) * self.damage_multiplier(is_spell, is_dist)
+            }
+            Effect::LifeHit { element, bounds } => {
+                // Damages based on caster's life are not boosted by any
+                // characteristic
+                let damage = f64::from(self.get_carac(&CaracKind::Vitality))
+                    * (f64::from(*bounds.start()) + f64::from(*bounds.end()))
+                    / 200.;
 
-                if is_dist {
-                    damage *= 1.
-                        + f64::from(
-                            self.get_carac(&CaracKind::PerRangedDamage),
-                        ) / 100.
-                } else {
-                    damage *= 1.
-                        + f64::from(self.get_carac(&CaracKind::PerMeleeDamage))
-                            / 100.
+                self.apply_resistances(
+                    damage, *element, is_crit, is_dist, target,
+                )
+            }
+            Effect::Trap { element, bounds } => {
+                let trap_pow_stats = self.eval(&RawCaracsValue::TrapPowStats(
+                    element.effective_stat(),
+                ));
+                let mut damage: f64 = trap_pow_stats
+                    * (f64::from(*bounds.start()) + f64::from(*bounds.end()))
+                    / 200.;
+
+                damage += f64::from(self.get_carac(&CaracKind::TrapDamage));
+
+                if is_crit {
+                    damage +=
+                        f64::from(self.get_carac(&CaracKind::CriticalDamage));
                 }
 
-                damage
+                self.apply_resistances(
+                    damage, *element, is_crit, is_dist, target,
+                ) * self.damage_multiplier(is_spell, is_dist)
             }
+            Effect::Pushback { cells } => self.pushback_damage(*cells, target),
+            Effect::Heal { .. } | Effect::Shield { .. } => 0.,
+        }
+    }
+
+    /// Damages dealt to a target pushed back into an obstacle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::*;
+    /// use dofus_stuff::dofapi::Target;
+    ///
+    /// let db_sets = HashMap::new();
+    /// let caracs = Character::new(200, &db_sets).get_caracs();
+    /// let mut target = Target::default();
+    /// assert_eq!(caracs.pushback_damage(2, &target), 66.);
+    ///
+    /// target.pushback_resistance = 200;
+    /// assert_eq!(caracs.pushback_damage(2, &target), 0.);
+    /// ```
+    pub fn pushback_damage(&self, cells: u8, target: &Target) -> f64 {
+        let per_cell = f64::from(self.level) / 2.
+            + f64::from(self.get_carac(&CaracKind::PushbackDamage))
+            - f64::from(target.pushback_resistance)
+            + 32.;
+
+        (per_cell * f64::from(cells) / 4.).max(0.)
+    }
+
+    /// Multiplier applied by the attacker's percent damages.
+    pub fn damage_multiplier(&self, is_spell: bool, is_dist: bool) -> f64 {
+        let per_damage = |kind| 1. + f64::from(self.get_carac(kind)) / 100.;
+
+        let type_multiplier = if is_spell {
+            per_damage(&CaracKind::PerSpellDamage)
+        } else {
+            per_damage(&CaracKind::PerWeaponDamage)
+        };
+
+        let range_multiplier = if is_dist {
+            per_damage(&CaracKind::PerRangedDamage)
+        } else {
+            per_damage(&CaracKind::PerMeleeDamage)
+        };
+
+        type_multiplier * range_multiplier
+    }
+
+    /// Apply resistances of the target to some damages.
+    pub fn apply_resistances(
+        &self,
+        damage: f64,
+        element: Element,
+        is_crit: bool,
+        is_dist: bool,
+        target: &Target,
+    ) -> f64 {
+        let mut damage = damage - f64::from(target.get_resistance(element));
+
+        if is_crit {
+            damage -= f64::from(target.critical_resistance);
+        }
+
+        damage = damage.max(0.)
+            * (1. - f64::from(target.get_per_resistance(element)) / 100.);
+
+        if is_dist {
+            damage *= 1. - f64::from(target.per_ranged_resistance) / 100.;
+        } else {
+            damage *= 1. - f64::from(target.per_melee_resistance) / 100.;
+        }
+
+        damage * target.final_multiplier
+    }
+
+    /// Average heals of an effect against a target, heals are boosted by
+    /// intelligence and power while lifesteal heals half of the damages
+    /// dealt.
+    pub fn mean_effect_heal(
+        &self,
+        effect: &Effect,
+        is_crit: bool,
+        is_spell: bool,
+        is_dist: bool,
+        target: &Target,
+    ) -> f64 {
+        match effect {
+            Effect::Heal { bounds } => {
+                self.eval(&RawCaracsValue::PowStats(Element::Fire))
+                    * (f64::from(*bounds.start()) + f64::from(*bounds.end()))
+                    / 200.
+                    + f64::from(self.get_carac(&CaracKind::Heals))
+            }
+            Effect::Hit {
+                lifesteal: true, ..
+            } => {
+                self.mean_effect_damage(
+                    effect, is_crit, is_spell, is_dist, target,
+                ) / 2.
+            }
+            _ => 0.,
+        }
+    }
+
+    /// Average shield points of an effect, which only depend on the level of
+    /// the character.
+    pub fn mean_effect_shield(&self, effect: &Effect) -> f64 {
+        match effect {
+            Effect::Shield { bounds } => {
+                f64::from(self.level)
+                    * (f64::from(*bounds.start()) + f64::from(*bounds.end()))
+                    / 200.
+            }
+            _ => 0.,
         }
     }
 
     pub fn mean_weapon_damage(&self, effects: &SpellEffects) -> f64 {
-        let mean_dmg_nocrit: f64 = effects
+        self.mean_damage_against(effects, &Target::default())
+    }
+
+    /// Average damages of a spell against a target, taking critical hits
+    /// into account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::*;
+    /// use dofus_stuff::dofapi::{SpellEffects, Target};
+    ///
+    /// let spell: SpellEffects = serde_json::from_str(
+    ///     r#"{
+    ///         "critical": 0,
+    ///         "ranged": true,
+    ///         "effect": [{"Hit": {"element": "Air", "bounds": [10, 10]}}]
+    ///     }"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let target: Target = serde_json::from_str(
+    ///     r#"{
+    ///         "resistance": {"Air": 2},
+    ///         "per_resistance": {"Air": 50},
+    ///         "per_ranged_resistance": 50
+    ///     }"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let db_sets = HashMap::new();
+    /// let caracs = Character::new(200, &db_sets).get_caracs();
+    /// assert_eq!(caracs.mean_weapon_damage(&spell), 10.);
+    /// assert_eq!(caracs.mean_damage_against(&spell, &target), 2.);
+    /// ```
+    pub fn mean_damage_against(
+        &self,
+        effects: &SpellEffects,
+        target: &Target,
+    ) -> f64 {
+        self.mean_spell_value(effects, |effect, is_crit| {
+            self.mean_effect_damage(
+                effect,
+                is_crit,
+                !effects.weapon,
+                effects.ranged,
+                target,
+            )
+        })
+    }
+
+    /// Best average damages that can be dealt during a turn by casting the
+    /// given spells with the AP of the character, each spell being cast at
+    /// most `casts_per_turn` times.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::*;
+    /// use dofus_stuff::dofapi::{CaracKind, SpellLevel};
+    ///
+    /// let parse_spell = |ap_cost, casts, damage| -> SpellLevel {
+    ///     serde_json::from_str(&format!(
+    ///         r#"{{
+    ///             "ap_cost": {}, "range": [1, 1], "casts_per_turn": {},
+    ///             "critical": 0, "ranged": false, "effect": [
+    ///                 {{"Hit": {{"element": "Air", "bounds": [{}, {}]}}}}
+    ///             ]
+    ///         }}"#,
+    ///         ap_cost, casts, damage, damage
+    ///     ))
+    ///     .unwrap()
+    /// };
+    ///
+    /// let big_hit = parse_spell(4, "1", 10);
+    /// let small_hit = parse_spell(3, "null", 6);
+    ///
+    /// let db_sets = HashMap::new();
+    /// let caracs = Character::new(200, &db_sets).get_caracs();
+    /// assert_eq!(caracs.get_carac(&CaracKind::AP), 8);
+    /// assert_eq!(caracs.turn_damage(vec![&big_hit, &small_hit]), 16.);
+    /// assert_eq!(caracs.turn_damage(vec![&small_hit]), 12.);
+    /// ```
+    pub fn turn_damage<'s>(
+        &self,
+        spells: impl IntoIterator<Item = &'s SpellLevel>,
+    ) -> f64 {
+        self.turn_plan(spells)
+            .into_iter()
+            .map(|spell| self.mean_weapon_damage(&spell.effects))
+            .sum()
+    }
+
+    /// List of casts that deal the best average damages during a turn, see
+    /// `RawCaracs::turn_damage`.
+    pub fn turn_plan<'s>(
+        &self,
+        spells: impl IntoIterator<Item = &'s SpellLevel>,
+    ) -> Vec<&'s SpellLevel> {
+        let ap = std::cmp::max(self.get_carac(&CaracKind::AP), 0) as usize;
+
+        // Bounded knapsack over AP: `best[x]` is the best damages that can
+        // be dealt with at most `x` AP, together with the spells cast.
+        let mut best: Vec<(f64, Vec<&'s SpellLevel>)> =
+            vec![(0., Vec::new()); ap + 1];
+
+        for spell in spells {
+            let cost = usize::from(spell.ap_cost);
+            let damage = self.mean_weapon_damage(&spell.effects);
+            let max_casts = ap / std::cmp::max(cost, 1);
+            let casts = spell
+                .casts_per_turn
+                .map(|casts| std::cmp::min(usize::from(casts), max_casts))
+                .unwrap_or(max_casts);
+
+            for _ in 0..casts {
+                for budget in (cost..=ap).rev() {
+                    let with_spell = best[budget - cost].0 + damage;
+
+                    if with_spell > best[budget].0 {
+                        let mut plan = best[budget - cost].1.clone();
+                        plan.push(spell);
+                        best[budget] = (with_spell, plan);
+                    }
+                }
+            }
+        }
+
+        best.swap_remove(ap).1
+    }
+
+    /// Distribution of the damages of a spell, taking each possible roll of
+    /// its effects and critical hits into account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::*;
+    /// use dofus_stuff::dofapi::SpellEffects;
+    ///
+    /// let spell: SpellEffects = serde_json::from_str(
+    ///     r#"{
+    ///         "critical": 50,
+    ///         "ranged": true,
+    ///         "effect": [
+    ///             {"Hit": {"element": "Air", "bounds": [1, 2]}},
+    ///             {"Hit": {"element": "Fire", "bounds": [1, 2]}}
+    ///         ],
+    ///         "critical_effect": [
+    ///             {"Hit": {"element": "Air", "bounds": [10, 10]}}
+    ///         ]
+    ///     }"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let db_sets = HashMap::new();
+    /// let caracs = Character::new(200, &db_sets).get_caracs();
+    /// let distribution = caracs.damage_distribution(&spell);
+    ///
+    /// let mean = caracs.mean_weapon_damage(&spell);
+    /// assert!((distribution.mean() - mean).abs() < 1e-9);
+    /// assert_eq!(distribution.percentile(10.), 2.);
+    /// assert_eq!(distribution.percentile(50.), 4.);
+    /// assert!((distribution.chance_at_least(4.) - 0.625).abs() < 1e-9);
+    ///
+    /// // Bounds given in reverse order describe the same rolls
+    /// let hit = |bounds| -> SpellEffects {
+    ///     serde_json::from_str(&format!(
+    ///         r#"{{"ranged": true, "effect": [
+    ///             {{"Hit": {{"element": "Air", "bounds": {}}}}}
+    ///         ]}}"#,
+    ///         bounds
+    ///     ))
+    ///     .unwrap()
+    /// };
+    /// let sorted = caracs.damage_distribution(&hit("[5, 10]"));
+    /// let reversed = caracs.damage_distribution(&hit("[10, 5]"));
+    /// assert!(sorted.percentile(50.) > 0.);
+    /// assert_eq!(reversed.percentile(50.), sorted.percentile(50.));
+    /// assert_eq!(reversed.mean(), sorted.mean());
+    /// ```
+    pub fn damage_distribution(&self, effects: &SpellEffects) -> Distribution {
+        let is_spell = !effects.weapon;
+        let is_dist = effects.ranged;
+        let target = Target::default();
+
+        let sum_effects = |list: &[Effect], is_crit| {
+            list.iter()
+                .map(|effect| {
+                    Distribution::uniform(effect.each_roll().iter().map(
+                        |roll| {
+                            self.mean_effect_damage(
+                                roll, is_crit, is_spell, is_dist, &target,
+                            )
+                        },
+                    ))
+                })
+                .fold(Distribution::constant(0.), |acc, x| acc.add(&x))
+        };
+
+        let nocrit = sum_effects(&effects.effect, false);
+        let crit = sum_effects(&effects.critical_effect, true);
+        nocrit.mix(&crit, self.critical_probability(effects))
+    }
+
+    /// Distribution of the damages of a turn, following the casts given by
+    /// `RawCaracs::turn_plan`.
+    pub fn turn_damage_distribution<'s>(
+        &self,
+        spells: impl IntoIterator<Item = &'s SpellLevel>,
+    ) -> Distribution {
+        self.turn_plan(spells)
+            .into_iter()
+            .map(|spell| self.damage_distribution(&spell.effects))
+            .fold(Distribution::constant(0.), |acc, x| acc.add(&x))
+    }
+
+    /// Average heals of a spell, taking critical hits into account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::*;
+    /// use dofus_stuff::dofapi::SpellEffects;
+    ///
+    /// let spell: SpellEffects = serde_json::from_str(
+    ///     r#"{
+    ///         "critical": 10,
+    ///         "ranged": true,
+    ///         "effect": [{"Heal": {"bounds": [10, 20]}}],
+    ///         "critical_effect": [{"Heal": {"bounds": [20, 30]}}]
+    ///     }"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let lifesteal: SpellEffects = serde_json::from_str(
+    ///     r#"{
+    ///         "critical": 0,
+    ///         "ranged": true,
+    ///         "effect": [{"Hit": {
+    ///             "element": "Air",
+    ///             "bounds": [10, 10],
+    ///             "lifesteal": true
+    ///         }}]
+    ///     }"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let db_sets = HashMap::new();
+    /// let caracs = Character::new(200, &db_sets).get_caracs();
+    /// assert!((caracs.mean_heal(&spell) - 16.).abs() < 1e-9);
+    /// assert_eq!(caracs.mean_heal(&lifesteal), 5.);
+    /// ```
+    pub fn mean_heal(&self, effects: &SpellEffects) -> f64 {
+        let target = Target::default();
+
+        self.mean_spell_value(effects, |effect, is_crit| {
+            self.mean_effect_heal(
+                effect,
+                is_crit,
+                !effects.weapon,
+                effects.ranged,
+                &target,
+            )
+        })
+    }
+
+    /// Average shield points given by a spell, taking critical hits into
+    /// account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::*;
+    /// use dofus_stuff::dofapi::SpellEffects;
+    ///
+    /// let spell: SpellEffects = serde_json::from_str(
+    ///     r#"{
+    ///         "critical": 0,
+    ///         "ranged": true,
+    ///         "effect": [{"Shield": {"bounds": [150, 150]}}]
+    ///     }"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let db_sets = HashMap::new();
+    /// let caracs = Character::new(200, &db_sets).get_caracs();
+    /// assert_eq!(caracs.mean_shield(&spell), 300.);
+    /// ```
+    pub fn mean_shield(&self, effects: &SpellEffects) -> f64 {
+        self.mean_spell_value(effects, |effect, _is_crit| {
+            self.mean_effect_shield(effect)
+        })
+    }
+
+    /// Average value of a spell given the value of each of its effects,
+    /// taking critical hits into account.
+    fn mean_spell_value<F>(&self, effects: &SpellEffects, value: F) -> f64
+    where
+        F: Fn(&Effect, bool) -> f64,
+    {
+        let mean_nocrit: f64 = effects
             .effect
             .iter()
-            .map(|effect| {
-                self.mean_effect_damage(
-                    effect,
-                    false,
-                    !effects.weapon,
-                    effects.ranged,
-                )
-            })
+            .map(|effect| value(effect, false))
             .sum();
-        let mean_dmg_crit: f64 = effects
+        let mean_crit: f64 = effects
             .critical_effect
             .iter()
-            .map(|effect| {
-                self.mean_effect_damage(
-                    effect,
-                    true,
-                    !effects.weapon,
-                    effects.ranged,
-                )
-            })
+            .map(|effect| value(effect, true))
             .sum();
-        let crit: f64 = f64::from(
+        let crit = self.critical_probability(effects);
+        (1. - crit) * mean_nocrit + crit * mean_crit
+    }
+
+    /// Probability to land a critical hit with a spell.
+    pub fn critical_probability(&self, effects: &SpellEffects) -> f64 {
+        let crit = f64::from(
             i16::from(effects.critical) + self.get_carac(&CaracKind::Critical),
         ) / 100.;
-        (1. - crit) * mean_dmg_nocrit + crit * mean_dmg_crit
+        crit.clamp(0., 1.)
     }
 }
 
@@ -710,44 +1966,619 @@ impl RawCaracs<'_> {
 //
 
 /// Enumeration of values that can be computed from a `RawCaracs`.
+///
+/// Spells are given as `S`, which are complete spell levels for values that
+/// can be evaluated. Values read from an input may rather reference the spell
+/// database through `CastableSpell`, such values must be converted with
+/// `RawCaracsValue::resolve_spells` before evaluation.
 #[derive(Debug, Deserialize)]
-pub enum RawCaracsValue {
+pub enum RawCaracsValue<S = SpellLevel> {
     Carac(CaracKind),
     PowStats(Element),
+    TrapPowStats(Element),
     MeanExtraDamage(Element),
+    PushbackDamage(u8),
     MeanDamage(SpellEffects),
+    /// Average damages of a spell.
+    SpellDamage(S),
+    /// Best average damages of a turn using a list of spells.
+    TurnDamage(Vec<S>),
+    /// Damages of a spell that are reached with given probability.
+    DamagePercentile {
+        spell:      S,
+        percentile: f64,
+    },
+    /// Chance in percent to deal at least some damages with a spell.
+    DamageChance {
+        spell:     S,
+        threshold: f64,
+    },
+    TurnDamagePercentile {
+        spells:     Vec<S>,
+        percentile: f64,
+    },
+    TurnDamageChance {
+        spells:    Vec<S>,
+        threshold: f64,
+    },
+    TargetDamage {
+        spell:  S,
+        target: Target,
+    },
+    MeanHeal(S),
+    MeanShield(S),
+    APRemoval(Removal),
+    MPRemoval(Removal),
+    Escape(i16),
+    Tackle(i16),
     PerResVariance,
     Resiliance,
+    EffectiveHealth(DamageProfile),
+    /// Smallest of several values.
+    Min(Vec<RawCaracsValue<S>>),
+    /// Greatest of several values.
+    Max(Vec<RawCaracsValue<S>>),
+    Sum(Vec<RawCaracsValue<S>>),
+    WeightedSum(Vec<(RawCaracsValue<S>, f64)>),
+    /// Quotient of two values, zero if the denominator is zero.
+    Ratio(Box<RawCaracsValue<S>>, Box<RawCaracsValue<S>>),
+}
+
+/// Gather influences over the same characteristics together.
+pub fn merge_influences(
+    influences: impl IntoIterator<Item = (CaracKind, Influence)>,
+) -> Vec<(CaracKind, Influence)> {
+    let mut merged: Vec<(CaracKind, Influence)> = Vec::new();
+
+    for (kind, influence) in influences {
+        match merged.iter_mut().find(|(other, _)| *other == kind) {
+            Some((_, other)) => *other = other.merge(influence),
+            None => merged.push((kind, influence)),
+        }
+    }
+
+    merged
+}
+
+/// Description of the damages a character expects to take.
+#[derive(Debug, Deserialize)]
+pub struct DamageProfile {
+    /// Share of damages dealt in each element, all elements are equally
+    /// weighted if empty.
+    #[serde(default)]
+    pub elements: HashMap<Element, f64>,
+
+    /// Typical size of a hit, fixed resistances are ignored if not set.
+    #[serde(default)]
+    pub hit: Option<f64>,
+
+    /// Share of melee damages, between 0 and 1.
+    #[serde(default = "default_melee_share")]
+    pub melee: f64,
+}
+
+impl DamageProfile {
+    pub fn get_weight(&self, element: Element) -> f64 {
+        if self.elements.is_empty() {
+            1.
+        } else {
+            *self.elements.get(&element).unwrap_or(&0.)
+        }
+    }
+}
+
+fn default_melee_share() -> f64 {
+    0.5
+}
+
+/// An attempt to remove AP or MP from an opponent.
+#[derive(Debug, Deserialize)]
+pub struct Removal {
+    /// Number of points the spell tries to remove.
+    pub points: u8,
+
+    /// AP or MP resistance of the opponent.
+    pub resistance: i16,
+
+    /// Total AP or MP of the opponent, defaults to 12 AP or 6 MP.
+    #[serde(default)]
+    pub total: Option<u8>,
 }
 
 impl RawCaracsValue {
+    #[allow(clippy::result_unit_err)]
     pub fn approx_smithmage_weight(&self) -> Result<f64, ()> {
         Ok(match self {
+            RawCaracsValue::Min(values)
+            | RawCaracsValue::Max(values)
+            | RawCaracsValue::Sum(values) => {
+                Self::mean_weight(values.iter().map(|value| (value, 1.)))?
+            }
+            RawCaracsValue::WeightedSum(values) => Self::mean_weight(
+                values.iter().map(|(value, weight)| (value, *weight)),
+            )?,
+            RawCaracsValue::Ratio(num, den) => {
+                // The denominator is approximated by the width of its target
+                100. * num.approx_smithmage_weight()?
+                    / den.approx_smithmage_weight()?
+            }
             RawCaracsValue::Carac(kind) => kind.smithmage_weight()?,
-            RawCaracsValue::Resiliance => {
+            RawCaracsValue::Resiliance
+            | RawCaracsValue::EffectiveHealth(_) => {
                 0.75 * CaracKind::Vitality.smithmage_weight().unwrap()
             }
-            RawCaracsValue::PowStats(elem) => {
+            RawCaracsValue::PowStats(elem)
+            | RawCaracsValue::TrapPowStats(elem) => {
                 CaracKind::Stats(*elem).smithmage_weight().unwrap()
             }
+            RawCaracsValue::APRemoval(removal)
+            | RawCaracsValue::MPRemoval(removal) => {
+                // Each point of reduction adds about `50 / resistance`
+                // percents of chance
+                CaracKind::APReduction.smithmage_weight().unwrap()
+                    * f64::from(std::cmp::max(removal.resistance, 1))
+                    / 50.
+            }
+            RawCaracsValue::Escape(opponent)
+            | RawCaracsValue::Tackle(opponent) => {
+                // Each point of dodge or lock changes the ratio by
+                // `50 / (opponent + 2)` percents
+                CaracKind::Dodge.smithmage_weight().unwrap()
+                    * f64::from(std::cmp::max(i32::from(*opponent) + 2, 1))
+                    / 50.
+            }
+            RawCaracsValue::PushbackDamage(cells) => {
+                // Each point of pushback damage adds `cells / 4` damages
+                4. * CaracKind::PushbackDamage.smithmage_weight().unwrap()
+                    / f64::from(std::cmp::max(*cells, 1))
+            }
             RawCaracsValue::MeanExtraDamage(elem) => {
                 CaracKind::Damage(*elem).smithmage_weight().unwrap()
             }
+            RawCaracsValue::DamageChance { threshold, .. }
+            | RawCaracsValue::TurnDamageChance { threshold, .. } => {
+                // Each percent of chance is worth about a hundredth of the
+                // threshold damages
+                CaracKind::Vitality.smithmage_weight().unwrap() * threshold
+                    / 100.
+            }
             RawCaracsValue::PerResVariance => {
                 CaracKind::PerResistance(Element::Neutral)
                     .smithmage_weight()
                     .unwrap()
             }
-            RawCaracsValue::MeanDamage(_) => {
+            RawCaracsValue::MeanDamage(_)
+            | RawCaracsValue::SpellDamage(_)
+            | RawCaracsValue::TurnDamage(_)
+            | RawCaracsValue::DamagePercentile { .. }
+            | RawCaracsValue::TurnDamagePercentile { .. }
+            | RawCaracsValue::TargetDamage { .. }
+            | RawCaracsValue::MeanHeal(_)
+            | RawCaracsValue::MeanShield(_) => {
                 CaracKind::Vitality.smithmage_weight().unwrap()
             }
         })
     }
 
-    pub fn is_decreasing(&self) -> bool {
+    /// Average weight of a list of weighted values, each value varying
+    /// `1 / weight` times slower than the aggregate.
+    fn mean_weight<'v>(
+        values: impl Iterator<Item = (&'v Self, f64)>,
+    ) -> Result<f64, ()> {
+        let mut count = 0;
+        let mut total = 0.;
+
+        for (value, weight) in values.filter(|(_, weight)| *weight != 0.) {
+            count += 1;
+            total += value.approx_smithmage_weight()? / weight.abs();
+        }
+
+        if count == 0 {
+            Err(())
+        } else {
+            Ok(total / f64::from(count))
+        }
+    }
+
+    /// Characteristics this value depends on, with the way they influence it.
+    /// Values that depend on too many characteristics, like damages, are
+    /// assumed to increase with all of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dofus_stuff::character::RawCaracsValue;
+    /// use dofus_stuff::dofapi::{CaracKind::*, Influence};
+    ///
+    /// let value: RawCaracsValue = serde_json::from_str(
+    ///     r#"{"Ratio": [{"Carac": "Vitality"}, {"Carac": "AP"}]}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     value.influences(),
+    ///     [(Vitality, Influence::Increasing), (AP, Influence::Decreasing)]
+    /// );
+    /// ```
+    pub fn influences(&self) -> Vec<(CaracKind, Influence)> {
+        let increasing = |kinds: Vec<CaracKind>| {
+            kinds
+                .into_iter()
+                .map(|kind| (kind, Influence::Increasing))
+                .collect()
+        };
+
+        let per_resistances = || {
+            Element::ALL
+                .iter()
+                .map(|&elem| CaracKind::PerResistance(elem))
+                .collect::<Vec<_>>()
+        };
+
+        match self {
+            RawCaracsValue::Carac(kind) => {
+                increasing(RawCaracs::carac_sources(kind))
+            }
+            RawCaracsValue::PowStats(elem) => {
+                let stats = CaracKind::Stats(elem.effective_stat());
+                increasing(vec![stats, CaracKind::Power])
+            }
+            RawCaracsValue::TrapPowStats(elem) => {
+                let stats = CaracKind::Stats(elem.effective_stat());
+                increasing(vec![stats, CaracKind::TrapPower])
+            }
+            RawCaracsValue::APRemoval(_) => {
+                increasing(RawCaracs::carac_sources(&CaracKind::APReduction))
+            }
+            RawCaracsValue::MPRemoval(_) => {
+                increasing(RawCaracs::carac_sources(&CaracKind::MPReduction))
+            }
+            RawCaracsValue::Escape(_) => {
+                increasing(RawCaracs::carac_sources(&CaracKind::Dodge))
+            }
+            RawCaracsValue::Tackle(_) => {
+                increasing(RawCaracs::carac_sources(&CaracKind::Lock))
+            }
+            RawCaracsValue::PerResVariance => per_resistances()
+                .into_iter()
+                .map(|kind| (kind, Influence::Any))
+                .collect(),
+            RawCaracsValue::Resiliance => {
+                let mut kinds = per_resistances();
+                kinds.push(CaracKind::Vitality);
+                increasing(kinds)
+            }
+            RawCaracsValue::EffectiveHealth(_) => {
+                let mut kinds = per_resistances();
+                kinds.extend(
+                    Element::ALL
+                        .iter()
+                        .map(|&elem| CaracKind::Resistance(elem)),
+                );
+                kinds.push(CaracKind::Vitality);
+                kinds.push(CaracKind::PerMeleeResistance);
+                kinds.push(CaracKind::PerRangedResistance);
+                increasing(kinds)
+            }
+            RawCaracsValue::MeanExtraDamage(_)
+            | RawCaracsValue::PushbackDamage(_)
+            | RawCaracsValue::MeanDamage(_)
+            | RawCaracsValue::SpellDamage(_)
+            | RawCaracsValue::TurnDamage(_)
+            | RawCaracsValue::DamagePercentile { .. }
+            | RawCaracsValue::DamageChance { .. }
+            | RawCaracsValue::TurnDamagePercentile { .. }
+            | RawCaracsValue::TurnDamageChance { .. }
+            | RawCaracsValue::TargetDamage { .. }
+            | RawCaracsValue::MeanHeal(_)
+            | RawCaracsValue::MeanShield(_) => increasing(
+                (0..CARAC_KIND_COUNT)
+                    .filter_map(CaracKind::from_index)
+                    .collect(),
+            ),
+            RawCaracsValue::Min(values)
+            | RawCaracsValue::Max(values)
+            | RawCaracsValue::Sum(values) => merge_influences(
+                values.iter().flat_map(|value| value.influences()),
+            ),
+            RawCaracsValue::WeightedSum(values) => merge_influences(
+                values.iter().filter(|(_, weight)| *weight != 0.).flat_map(
+                    |(value, weight)| {
+                        value.influences().into_iter().map(
+                            move |(kind, influence)| {
+                                if *weight < 0. {
+                                    (kind, influence.reverse())
+                                } else {
+                                    (kind, influence)
+                                }
+                            },
+                        )
+                    },
+                ),
+            ),
+            RawCaracsValue::Ratio(num, den) => merge_influences(
+                num.influences().into_iter().chain(
+                    den.influences()
+                        .into_iter()
+                        .map(|(kind, influence)| (kind, influence.reverse())),
+                ),
+            ),
+        }
+    }
+
+    /// Direction in which the value is better: aggregates combine the
+    /// directions of their members, reversed by negative weights and in the
+    /// denominator of ratios, and are `Influence::Any` if they disagree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dofus_stuff::character::RawCaracsValue;
+    /// use dofus_stuff::dofapi::Influence;
+    ///
+    /// let direction = |json| {
+    ///     serde_json::from_str::<RawCaracsValue>(json).unwrap().direction()
+    /// };
+    ///
+    /// assert_eq!(
+    ///     direction(r#"{"WeightedSum": [["PerResVariance", -1]]}"#),
+    ///     Influence::Increasing
+    /// );
+    /// assert_eq!(
+    ///     direction(r#"{"WeightedSum": [[{"Carac": "Vitality"}, -1]]}"#),
+    ///     Influence::Decreasing
+    /// );
+    /// assert_eq!(
+    ///     direction(r#"{"Ratio": [{"Carac": "AP"}, "PerResVariance"]}"#),
+    ///     Influence::Increasing
+    /// );
+    /// assert_eq!(
+    ///     direction(r#"{"Sum": [{"Carac": "AP"}, "PerResVariance"]}"#),
+    ///     Influence::Any
+    /// );
+    /// ```
+    pub fn direction(&self) -> Influence {
+        fn merge(directions: impl Iterator<Item = Influence>) -> Influence {
+            directions
+                .reduce(Influence::merge)
+                .unwrap_or(Influence::Increasing)
+        }
+
         match self {
-            Self::PerResVariance => true,
-            _ => false,
+            RawCaracsValue::Min(values)
+            | RawCaracsValue::Max(values)
+            | RawCaracsValue::Sum(values) => {
+                merge(values.iter().map(Self::direction))
+            }
+            RawCaracsValue::WeightedSum(values) => {
+                merge(values.iter().filter(|(_, weight)| *weight != 0.).map(
+                    |(value, weight)| {
+                        if *weight < 0. {
+                            value.direction().reverse()
+                        } else {
+                            value.direction()
+                        }
+                    },
+                ))
+            }
+            RawCaracsValue::Ratio(num, den) => {
+                num.direction().merge(den.direction().reverse())
+            }
+            RawCaracsValue::PerResVariance => Influence::Decreasing,
+            _ => Influence::Increasing,
         }
     }
+
+    /// Check if lower values are better, values with no clear direction are
+    /// maximized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::*;
+    ///
+    /// let value: RawCaracsValue = serde_json::from_str(
+    ///     r#"{"Min": [
+    ///         {"PowStats": "Air"},
+    ///         {"WeightedSum": [
+    ///             [{"Carac": "Agility"}, 2],
+    ///             ["Resiliance", 1]
+    ///         ]},
+    ///         {"Ratio": [{"Carac": "AP"}, {"Carac": "MP"}]}
+    ///     ]}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let db_sets = HashMap::new();
+    /// let caracs = Character::new(200, &db_sets).get_caracs();
+    /// assert_eq!(caracs.eval(&value), 2.);
+    /// assert!(value.approx_smithmage_weight().is_ok());
+    /// assert!(!value.is_decreasing());
+    /// ```
+    pub fn is_decreasing(&self) -> bool {
+        self.direction() == Influence::Decreasing
+    }
+}
+
+impl RawCaracsValue<CastableSpell> {
+    /// Replace references to the spell database with the corresponding
+    /// spells, which makes the value ready for evaluation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use dofus_stuff::character::{Character, RawCaracsValue};
+    /// use dofus_stuff::dofapi::{CastableSpell, Spell, SpellError};
+    ///
+    /// let spells: Vec<Spell> = serde_json::from_str(
+    ///     r#"[{
+    ///         "name": "Ambush",
+    ///         "breed": "Cra",
+    ///         "levels": [{
+    ///             "ap_cost": 4,
+    ///             "range": [1, 8],
+    ///             "ranged": true,
+    ///             "effect": [{"Hit": {"element": "Air", "bounds": [7, 9]}}]
+    ///         }]
+    ///     }]"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let value: RawCaracsValue<CastableSpell> = serde_json::from_str(
+    ///     r#"{"SpellDamage": {"spell": "Ambush", "level": 1}}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let value = value.resolve_spells(&spells).unwrap();
+    /// assert!(matches!(value, RawCaracsValue::SpellDamage(_)));
+    ///
+    /// let db_sets = HashMap::new();
+    /// let caracs = Character::new(200, &db_sets).get_caracs();
+    /// assert!(caracs.eval(&value) > 0.);
+    ///
+    /// let value: RawCaracsValue<CastableSpell> = serde_json::from_str(
+    ///     r#"{"DamagePercentile": {
+    ///         "spell": {"spell": "Ambush", "level": 1},
+    ///         "percentile": 10
+    ///     }}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let value = value.resolve_spells(&spells).unwrap();
+    /// assert!(matches!(value, RawCaracsValue::DamagePercentile { .. }));
+    ///
+    /// // References nested in other values are resolved as well
+    /// let value: RawCaracsValue<CastableSpell> = serde_json::from_str(
+    ///     r#"{"Max": [{"SpellDamage": {"spell": "Unknown", "level": 1}}]}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     value.resolve_spells(&spells).unwrap_err(),
+    ///     SpellError::UnknownSpell("Unknown".to_string())
+    /// );
+    ///
+    /// // Unresolved references can't be read as values ready for evaluation
+    /// assert!(serde_json::from_str::<RawCaracsValue>(
+    ///     r#"{"SpellDamage": {"spell": "Ambush", "level": 1}}"#,
+    /// )
+    /// .is_err());
+    /// ```
+    pub fn resolve_spells(
+        self,
+        spells: &[Spell],
+    ) -> Result<RawCaracsValue, SpellError> {
+        let resolve_all = |castable: Vec<CastableSpell>| {
+            castable
+                .into_iter()
+                .map(|spell| spell.resolve(spells))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let resolve_values = |values: Vec<Self>| {
+            values
+                .into_iter()
+                .map(|value| value.resolve_spells(spells))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(match self {
+            RawCaracsValue::Carac(kind) => RawCaracsValue::Carac(kind),
+            RawCaracsValue::PowStats(elem) => RawCaracsValue::PowStats(elem),
+            RawCaracsValue::TrapPowStats(elem) => {
+                RawCaracsValue::TrapPowStats(elem)
+            }
+            RawCaracsValue::MeanExtraDamage(elem) => {
+                RawCaracsValue::MeanExtraDamage(elem)
+            }
+            RawCaracsValue::PushbackDamage(cells) => {
+                RawCaracsValue::PushbackDamage(cells)
+            }
+            RawCaracsValue::MeanDamage(effects) => {
+                RawCaracsValue::MeanDamage(effects)
+            }
+            RawCaracsValue::SpellDamage(spell) => {
+                RawCaracsValue::SpellDamage(spell.resolve(spells)?)
+            }
+            RawCaracsValue::TurnDamage(castable) => {
+                RawCaracsValue::TurnDamage(resolve_all(castable)?)
+            }
+            RawCaracsValue::DamagePercentile { spell, percentile } => {
+                RawCaracsValue::DamagePercentile {
+                    spell: spell.resolve(spells)?,
+                    percentile,
+                }
+            }
+            RawCaracsValue::DamageChance { spell, threshold } => {
+                RawCaracsValue::DamageChance {
+                    spell: spell.resolve(spells)?,
+                    threshold,
+                }
+            }
+            RawCaracsValue::TurnDamagePercentile {
+                spells: castable,
+                percentile,
+            } => RawCaracsValue::TurnDamagePercentile {
+                spells: resolve_all(castable)?,
+                percentile,
+            },
+            RawCaracsValue::TurnDamageChance {
+                spells: castable,
+                threshold,
+            } => RawCaracsValue::TurnDamageChance {
+                spells: resolve_all(castable)?,
+                threshold,
+            },
+            RawCaracsValue::TargetDamage { spell, target } => {
+                RawCaracsValue::TargetDamage {
+                    spell: spell.resolve(spells)?,
+                    target,
+                }
+            }
+            RawCaracsValue::MeanHeal(spell) => {
+                RawCaracsValue::MeanHeal(spell.resolve(spells)?)
+            }
+            RawCaracsValue::MeanShield(spell) => {
+                RawCaracsValue::MeanShield(spell.resolve(spells)?)
+            }
+            RawCaracsValue::APRemoval(removal) => {
+                RawCaracsValue::APRemoval(removal)
+            }
+            RawCaracsValue::MPRemoval(removal) => {
+                RawCaracsValue::MPRemoval(removal)
+            }
+            RawCaracsValue::Escape(lock) => RawCaracsValue::Escape(lock),
+            RawCaracsValue::Tackle(dodge) => RawCaracsValue::Tackle(dodge),
+            RawCaracsValue::PerResVariance => RawCaracsValue::PerResVariance,
+            RawCaracsValue::Resiliance => RawCaracsValue::Resiliance,
+            RawCaracsValue::EffectiveHealth(profile) => {
+                RawCaracsValue::EffectiveHealth(profile)
+            }
+            RawCaracsValue::Min(values) => {
+                RawCaracsValue::Min(resolve_values(values)?)
+            }
+            RawCaracsValue::Max(values) => {
+                RawCaracsValue::Max(resolve_values(values)?)
+            }
+            RawCaracsValue::Sum(values) => {
+                RawCaracsValue::Sum(resolve_values(values)?)
+            }
+            RawCaracsValue::WeightedSum(values) => {
+                RawCaracsValue::WeightedSum(
+                    values
+                        .into_iter()
+                        .map(|(value, weight)| {
+                            Ok((value.resolve_spells(spells)?, weight))
+                        })
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            RawCaracsValue::Ratio(num, den) => RawCaracsValue::Ratio(
+                Box::new(num.resolve_spells(spells)?),
+                Box::new(den.resolve_spells(spells)?),
+            ),
+        })
+    }
 }
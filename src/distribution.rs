@@ -0,0 +1,154 @@
+/// Maximal number of values kept in a distribution, closest values are merged
+/// together beyond this size.
+const MAX_SUPPORT: usize = 64;
+
+/// Discrete probability distribution over real values.
+///
+/// # Examples
+///
+/// ```
+/// use dofus_stuff::distribution::Distribution;
+///
+/// let dice = Distribution::uniform(1..=6);
+/// let two_dices = dice.add(&dice);
+///
+/// assert!((two_dices.mean() - 7.).abs() < 1e-9);
+/// assert_eq!(two_dices.percentile(50.), 7.);
+/// assert!((two_dices.chance_at_least(11.) - 3. / 36.).abs() < 1e-9);
+///
+/// let crit = Distribution::constant(20.);
+/// let spell = dice.mix(&crit, 0.5);
+/// assert!((spell.mean() - 11.75).abs() < 1e-9);
+/// assert_eq!(spell.percentile(100.), 20.);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Distribution {
+    /// Sorted list of values with their probabilities.
+    support: Vec<(f64, f64)>,
+}
+
+impl Distribution {
+    pub fn constant(value: f64) -> Self {
+        Self {
+            support: vec![(value, 1.)],
+        }
+    }
+
+    /// Uniform distribution over a list of values.
+    pub fn uniform(values: impl IntoIterator<Item = impl Into<f64>>) -> Self {
+        let values: Vec<f64> = values.into_iter().map(Into::into).collect();
+
+        if values.is_empty() {
+            return Self::constant(0.);
+        }
+
+        let prob = 1. / values.len() as f64;
+        Self::from_support(values.into_iter().map(|x| (x, prob)).collect())
+    }
+
+    /// Distribution of the sum of two independent variables.
+    pub fn add(&self, other: &Self) -> Self {
+        let support = self
+            .support
+            .iter()
+            .flat_map(|(x, p)| {
+                other.support.iter().map(move |(y, q)| (x + y, p * q))
+            })
+            .collect();
+
+        Self::from_support(support)
+    }
+
+    /// Take a value from `other` with probability `prob` and from `self`
+    /// otherwise.
+    pub fn mix(&self, other: &Self, prob: f64) -> Self {
+        let support = self
+            .support
+            .iter()
+            .map(|(x, p)| (*x, p * (1. - prob)))
+            .chain(other.support.iter().map(|(x, p)| (*x, p * prob)))
+            .collect();
+
+        Self::from_support(support)
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.support.iter().map(|(x, p)| x * p).sum()
+    }
+
+    pub fn variance(&self) -> f64 {
+        let mean = self.mean();
+        self.support
+            .iter()
+            .map(|(x, p)| p * (x - mean).powi(2))
+            .sum()
+    }
+
+    /// Smallest value such that the probability to get a lower or equal value
+    /// is at least `percentile` percents.
+    pub fn percentile(&self, percentile: f64) -> f64 {
+        let goal = percentile / 100. - 1e-9;
+        let mut cumulated = 0.;
+
+        for (x, p) in &self.support {
+            cumulated += p;
+
+            if cumulated >= goal {
+                return *x;
+            }
+        }
+
+        self.support.last().map(|(x, _)| *x).unwrap_or(0.)
+    }
+
+    /// Probability to get a value greater than or equal to `threshold`.
+    pub fn chance_at_least(&self, threshold: f64) -> f64 {
+        self.support
+            .iter()
+            .filter(|(x, _)| *x >= threshold - 1e-9)
+            .map(|(_, p)| p)
+            .sum()
+    }
+
+    /// Build a distribution from an unsorted list of values, which may contain
+    /// duplicates.
+    fn from_support(support: Vec<(f64, f64)>) -> Self {
+        let min = support
+            .iter()
+            .map(|(x, _)| *x)
+            .fold(f64::INFINITY, f64::min);
+        let max = support
+            .iter()
+            .map(|(x, _)| *x)
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        if support.is_empty() || max - min < 1e-9 {
+            let prob = support.iter().map(|(_, p)| p).sum();
+            return Self {
+                support: vec![(if min.is_finite() { min } else { 0. }, prob)],
+            };
+        }
+
+        // Values are spread into buckets of equal width, values falling into
+        // the same bucket are merged into their weighted mean
+        let scale = (MAX_SUPPORT - 1) as f64 / (max - min);
+        let mut buckets = vec![(0., 0.); MAX_SUPPORT];
+
+        for (x, p) in support {
+            let (value, prob) =
+                &mut buckets[((x - min) * scale).round() as usize];
+
+            if *prob <= 0. || (*value - x).abs() < 1e-9 {
+                *value = x;
+                *prob += p;
+            } else {
+                *value = (*value * *prob + x * p) / (*prob + p);
+                *prob += p;
+            }
+        }
+
+        Self {
+            support: buckets.into_iter().filter(|(_, p)| *p > 0.).collect(),
+        }
+    }
+}
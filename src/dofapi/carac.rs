@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 use std::convert::{From, Into};
 use std::fmt;
-use std::ops::RangeInclusive;
+use std::iter::FromIterator;
+use std::ops::{Add, AddAssign, RangeInclusive};
 
 use serde::{de, Deserialize, Deserializer};
 
@@ -55,7 +56,154 @@ pub enum CaracKind {
     Wisdom,
 }
 
+/// Way a characteristic influences a value.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Influence {
+    /// Greater values of the characteristic are better.
+    Increasing,
+    /// Lower values of the characteristic are better.
+    Decreasing,
+    /// The characteristic may be better or worse depending on its value.
+    Any,
+}
+
+impl Influence {
+    pub fn reverse(self) -> Self {
+        match self {
+            Influence::Increasing => Influence::Decreasing,
+            Influence::Decreasing => Influence::Increasing,
+            Influence::Any => Influence::Any,
+        }
+    }
+
+    /// Influence of a characteristic that is subject to both influences.
+    pub fn merge(self, other: Self) -> Self {
+        if self == other {
+            self
+        } else {
+            Influence::Any
+        }
+    }
+}
+
+/// Number of characteristics that are not `Special`, which is also the size
+/// of a `CaracVec`.
+pub const CARAC_KIND_COUNT: usize = 32 + 4 * Element::ALL.len();
+
 impl CaracKind {
+    /// Position of this characteristic in a `CaracVec`, `Special`
+    /// characteristics have no index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dofus_stuff::dofapi::{CaracKind, CARAC_KIND_COUNT};
+    ///
+    /// for index in 0..CARAC_KIND_COUNT {
+    ///     let kind = CaracKind::from_index(index).unwrap();
+    ///     assert_eq!(kind.index(), Some(index));
+    /// }
+    ///
+    /// assert_eq!(CaracKind::from_index(CARAC_KIND_COUNT), None);
+    /// assert_eq!(CaracKind::Special("Emote".to_string()).index(), None);
+    /// ```
+    pub fn index(&self) -> Option<usize> {
+        use CaracKind::*;
+        let elements = Element::ALL.len();
+
+        Some(match self {
+            AP => 0,
+            APReduction => 1,
+            APResistance => 2,
+            Critical => 3,
+            CriticalDamage => 4,
+            CriticalResistance => 5,
+            Dodge => 6,
+            Heals => 7,
+            Initiative => 8,
+            Lock => 9,
+            MP => 10,
+            MPReduction => 11,
+            MPResistance => 12,
+            PerMeleeDamage => 13,
+            PerMeleeResistance => 14,
+            PerRangedDamage => 15,
+            PerRangedResistance => 16,
+            PerSpellDamage => 17,
+            PerWeaponDamage => 18,
+            Pods => 19,
+            Power => 20,
+            Prospecting => 21,
+            PushbackDamage => 22,
+            PushbackResistance => 23,
+            Range => 24,
+            RawDamage => 25,
+            ReflectDamage => 26,
+            Summons => 27,
+            TrapDamage => 28,
+            TrapPower => 29,
+            Vitality => 30,
+            Wisdom => 31,
+            Damage(elem) => 32 + elem.index(),
+            PerResistance(elem) => 32 + elements + elem.index(),
+            Resistance(elem) => 32 + 2 * elements + elem.index(),
+            Stats(elem) => 32 + 3 * elements + elem.index(),
+            Special(_) => return None,
+        })
+    }
+
+    /// Get the characteristic stored at a given position of a `CaracVec`.
+    pub fn from_index(index: usize) -> Option<Self> {
+        use CaracKind::*;
+        let elements = Element::ALL.len();
+
+        Some(match index {
+            0 => AP,
+            1 => APReduction,
+            2 => APResistance,
+            3 => Critical,
+            4 => CriticalDamage,
+            5 => CriticalResistance,
+            6 => Dodge,
+            7 => Heals,
+            8 => Initiative,
+            9 => Lock,
+            10 => MP,
+            11 => MPReduction,
+            12 => MPResistance,
+            13 => PerMeleeDamage,
+            14 => PerMeleeResistance,
+            15 => PerRangedDamage,
+            16 => PerRangedResistance,
+            17 => PerSpellDamage,
+            18 => PerWeaponDamage,
+            19 => Pods,
+            20 => Power,
+            21 => Prospecting,
+            22 => PushbackDamage,
+            23 => PushbackResistance,
+            24 => Range,
+            25 => RawDamage,
+            26 => ReflectDamage,
+            27 => Summons,
+            28 => TrapDamage,
+            29 => TrapPower,
+            30 => Vitality,
+            31 => Wisdom,
+            _ if index < CARAC_KIND_COUNT => {
+                let elem = Element::ALL[(index - 32) % elements];
+                match (index - 32) / elements {
+                    0 => Damage(elem),
+                    1 => PerResistance(elem),
+                    2 => Resistance(elem),
+                    _ => Stats(elem),
+                }
+            }
+            _ => return None,
+        })
+    }
+
+    #[allow(clippy::result_unit_err)]
     pub fn smithmage_weight(&self) -> Result<f64, ()> {
         use CaracKind::*;
 
@@ -214,59 +362,273 @@ impl<'de> de::Visitor<'de> for CaracKindVisitor {
     }
 }
 
-// Deserializer for item line
+//   ____                __     __
+//  / ___|__ _ _ __ __ _  __\ \   / /__  ___
+// | |   / _` | '__/ _` |/ __\ \ / / _ \/ __|
+// | |__| (_| | | | (_| | (__ \ V /  __/ (__
+//  \____\__,_|_|  \__,_|\___| \_/ \___|\___|
+//
 
-#[derive(Clone, Debug)]
-pub struct CaracLines(HashMap<CaracKind, RangeInclusive<i16>>);
+/// A dense vector holding a value for each non-special characteristic.
+///
+/// # Examples
+///
+/// ```
+/// use dofus_stuff::dofapi::{CaracKind::*, CaracVec, Element::*};
+///
+/// let mut caracs = CaracVec::default();
+/// caracs.add(&Stats(Air), 100);
+///
+/// let mut other = CaracVec::default();
+/// other.add(&Stats(Air), 50);
+/// other.add(&Vitality, 200);
+///
+/// caracs += &other;
+/// assert_eq!(caracs.get(&Stats(Air)), 150);
+/// assert_eq!(caracs.get(&Vitality), 200);
+/// assert_eq!(caracs.get(&Special("Emote".to_string())), 0);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CaracVec([i16; CARAC_KIND_COUNT]);
+
+impl CaracVec {
+    /// Get the value of a characteristic, `Special` characteristics are
+    /// always zero.
+    pub fn get(&self, kind: &CaracKind) -> i16 {
+        let Self(values) = self;
+        kind.index().map(|index| values[index]).unwrap_or(0)
+    }
 
-impl CaracLines {
-    /// Use the carac lines as a map
-    pub fn as_map(&self) -> &HashMap<CaracKind, RangeInclusive<i16>> {
-        let Self(map) = self;
-        map
+    /// Increase the value of a characteristic, this has no effect for
+    /// `Special` characteristics.
+    pub fn add(&mut self, kind: &CaracKind, val: i16) {
+        let Self(values) = self;
+
+        if let Some(index) = kind.index() {
+            values[index] += val;
+        }
     }
 
-    /// Check if this item's statistics are greater than or equal to another
-    /// item. This is essentialy usefull to fix trophy conditions.
-    pub fn is_stronger_than(&self, other: &Self) -> bool {
-        other.as_map().iter().all(|(kind, other_bounds)| {
-            // Check if all stats of `other` are covered by this item.
-            self.as_map()
-                .get(kind)
-                .map(|self_bounds| self_bounds.start())
-                .unwrap_or(&0)
-                >= other_bounds.end()
-        }) && self.as_map().iter().all(|(kind, self_bounds)| {
-            // Check if all stats of this item are covered by `other`.
-            // This is required since there may be some negative values in this
-            // item that are not in `other`.
-            other
-                .as_map()
-                .get(kind)
-                .map(|other_bounds| other_bounds.end())
-                .unwrap_or(&0)
-                <= self_bounds.start()
-        })
+    /// Iterate over characteristics with a non-zero value.
+    pub fn iter(&self) -> impl Iterator<Item = (CaracKind, i16)> + '_ {
+        let Self(values) = self;
+        values
+            .iter()
+            .enumerate()
+            .filter(|(_, &val)| val != 0)
+            .map(|(index, &val)| (CaracKind::from_index(index).unwrap(), val))
     }
 }
 
-impl Default for CaracLines {
+impl Default for CaracVec {
     fn default() -> Self {
-        CaracLines(HashMap::new())
+        Self([0; CARAC_KIND_COUNT])
+    }
+}
+
+impl AddAssign<&CaracVec> for CaracVec {
+    fn add_assign(&mut self, other: &CaracVec) {
+        let (Self(values), Self(other)) = (self, other);
+
+        for (val, other) in values.iter_mut().zip(other.iter()) {
+            *val += other;
+        }
+    }
+}
+
+impl Add<&CaracVec> for CaracVec {
+    type Output = CaracVec;
+
+    fn add(mut self, other: &CaracVec) -> CaracVec {
+        self += other;
+        self
     }
 }
 
-impl From<HashMap<CaracKind, RangeInclusive<i16>>> for CaracLines {
-    fn from(map: HashMap<CaracKind, RangeInclusive<i16>>) -> Self {
-        Self(map)
+//   ____                _     _
+//  / ___|__ _ _ __ __ _| |   (_)_ __   ___  ___
+// | |   / _` | '__/ _` | |   | | '_ \ / _ \/ __|
+// | |__| (_| | | | (_| | |___| | | | |  __/\__ \
+//  \____\__,_|_|  \__,_|_____|_|_| |_|\___||___/
+//
+
+/// Characteristic lines of an item or of a set bonus.
+///
+/// Bounds of standard characteristics are stored in dense vectors while
+/// `Special` lines are kept aside.
+///
+/// # Examples
+///
+/// ```
+/// use dofus_stuff::dofapi::{CaracKind::*, CaracLines, Element::*};
+///
+/// let lines: CaracLines =
+///     vec![(Stats(Air), 40..=60), (Special("Emote".to_string()), 1..=1)]
+///         .into_iter()
+///         .collect();
+///
+/// assert_eq!(lines.get(&Stats(Air)), Some(40..=60));
+/// assert_eq!(lines.get(&Vitality), None);
+/// assert_eq!(lines.values().get(&Stats(Air)), 60);
+/// assert_eq!(lines.iter().count(), 2);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CaracLines {
+    min:     CaracVec,
+    max:     CaracVec,
+    special: Vec<(String, RangeInclusive<i16>)>,
+}
+
+impl CaracLines {
+    /// Get the bounds of a characteristic line, if any.
+    pub fn get(&self, kind: &CaracKind) -> Option<RangeInclusive<i16>> {
+        match kind {
+            CaracKind::Special(name) => self
+                .special
+                .iter()
+                .find(|(line, _)| line == name)
+                .map(|(_, bounds)| bounds.clone()),
+            _ => {
+                let (min, max) = (self.min.get(kind), self.max.get(kind));
+
+                if min == 0 && max == 0 {
+                    None
+                } else {
+                    Some(min..=max)
+                }
+            }
+        }
+    }
+
+    /// Best values that can be rolled for each standard characteristic.
+    pub fn values(&self) -> &CaracVec {
+        &self.max
+    }
+
+    /// Iterate over all lines, including `Special` ones.
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<Item = (CaracKind, RangeInclusive<i16>)> + '_ {
+        let (CaracVec(min), CaracVec(max)) = (&self.min, &self.max);
+        let standard = min
+            .iter()
+            .zip(max.iter())
+            .enumerate()
+            .filter(|(_, (&min, &max))| min != 0 || max != 0)
+            .map(|(index, (&min, &max))| {
+                (CaracKind::from_index(index).unwrap(), min..=max)
+            });
+        let special = self.special.iter().map(|(name, bounds)| {
+            (CaracKind::Special(name.clone()), bounds.clone())
+        });
+        standard.chain(special)
+    }
+
+    /// Insert a new line, replacing the previous one for the same
+    /// characteristic.
+    pub fn insert(&mut self, kind: CaracKind, bounds: RangeInclusive<i16>) {
+        let (start, end) = (*bounds.start(), *bounds.end());
+        let (min, max) =
+            (std::cmp::min(start, end), std::cmp::max(start, end));
+
+        match (kind.index(), kind) {
+            (Some(index), _) => {
+                let (CaracVec(min_vals), CaracVec(max_vals)) =
+                    (&mut self.min, &mut self.max);
+                min_vals[index] = min;
+                max_vals[index] = max;
+            }
+            (None, CaracKind::Special(name)) => {
+                self.special.retain(|(line, _)| *line != name);
+                self.special.push((name, min..=max));
+            }
+            (None, _) => unreachable!(),
+        }
+    }
+
+    /// Check if this item's statistics are greater than or equal to another
+    /// item. This is essentialy usefull to fix trophy conditions.
+    pub fn is_stronger_than(&self, other: &Self) -> bool {
+        let (CaracVec(self_min), CaracVec(other_max)) =
+            (&self.min, &other.max);
+
+        // Check that all stats of `other` are covered by this item. Since
+        // missing lines are zero this also checks that negative values of
+        // this item are covered by `other`.
+        self_min
+            .iter()
+            .zip(other_max.iter())
+            .all(|(self_min, other_max)| self_min >= other_max)
+            && other.special.iter().all(|(name, other_bounds)| {
+                self.get(&CaracKind::Special(name.clone()))
+                    .map(|self_bounds| *self_bounds.start())
+                    .unwrap_or(0)
+                    >= *other_bounds.end()
+            })
+            && self.special.iter().all(|(name, self_bounds)| {
+                other
+                    .get(&CaracKind::Special(name.clone()))
+                    .map(|other_bounds| *other_bounds.end())
+                    .unwrap_or(0)
+                    <= *self_bounds.start()
+            })
+    }
+
+    /// Check if this item's statistics are at least as good as another item
+    /// for given characteristics, all the other ones are ignored. This is a
+    /// relaxed version of `is_stronger_than`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dofus_stuff::dofapi::{CaracKind::*, CaracLines, Influence};
+    ///
+    /// let hat: CaracLines =
+    ///     vec![(Vitality, 200..=250), (AP, 1..=1)].into_iter().collect();
+    /// let cap: CaracLines =
+    ///     vec![(Vitality, 150..=200)].into_iter().collect();
+    ///
+    /// let vitality = [(Vitality, Influence::Increasing)];
+    /// assert!(hat.dominates(&cap, &vitality));
+    /// assert!(!cap.dominates(&hat, &vitality));
+    ///
+    /// let no_ap = [(Vitality, Influence::Increasing), (AP, Influence::Any)];
+    /// assert!(!hat.dominates(&cap, &no_ap));
+    /// ```
+    pub fn dominates(
+        &self,
+        other: &Self,
+        caracs: &[(CaracKind, Influence)],
+    ) -> bool {
+        caracs.iter().all(|(kind, influence)| {
+            let (self_min, self_max) =
+                (self.min.get(kind), self.max.get(kind));
+            let (other_min, other_max) =
+                (other.min.get(kind), other.max.get(kind));
+
+            match influence {
+                Influence::Increasing => self_min >= other_max,
+                Influence::Decreasing => self_max <= other_min,
+                Influence::Any => {
+                    self_min >= other_max && self_max <= other_min
+                }
+            }
+        })
     }
 }
 
-#[allow(clippy::implicit_hasher)]
-impl From<CaracLines> for HashMap<CaracKind, RangeInclusive<i16>> {
-    fn from(caracs: CaracLines) -> Self {
-        let CaracLines(map) = caracs;
-        map
+impl FromIterator<(CaracKind, RangeInclusive<i16>)> for CaracLines {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (CaracKind, RangeInclusive<i16>)>,
+    {
+        let mut ret = Self::default();
+
+        for (kind, bounds) in iter {
+            ret.insert(kind, bounds);
+        }
+
+        ret
     }
 }
 
@@ -292,7 +654,7 @@ impl<'de> de::Visitor<'de> for CaracLinesVisitor {
     where
         D: de::SeqAccess<'de>,
     {
-        let mut ret = HashMap::new();
+        let mut ret = CaracLines::default();
 
         #[derive(Deserialize)]
         struct Bounds {
@@ -328,6 +690,6 @@ impl<'de> de::Visitor<'de> for CaracLinesVisitor {
             }
         }
 
-        Ok(CaracLines(ret))
+        Ok(ret)
     }
 }
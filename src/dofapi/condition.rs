@@ -59,6 +59,17 @@ impl From<&str> for ConditionAtom {
     }
 }
 
+/// Check if a clause is stronger than another. Note that this is a partial
+/// order, two clauses may not be comparable.
+fn clause_stronger_than(
+    clause1: &[ConditionAtom],
+    clause2: &[ConditionAtom],
+) -> bool {
+    clause1
+        .iter()
+        .all(|atom1| clause2.iter().any(|atom2| atom1.is_stronger_than(atom2)))
+}
+
 /// A clause of atoms, i.e. written in the form (atom1 or atom2 ...) and
 /// (atom_i or atom_j ...) ...
 #[derive(Clone, Debug, Default)]
@@ -86,16 +97,6 @@ impl Condition {
     /// Build a clause which is true if and only if both `cond1` and `cond2`
     /// are true.
     pub fn and(cond1: Self, cond2: Self) -> Self {
-        // Function to check if a clause is stronger than another. Note that
-        // this is a partial order, two clauses may not be comparable.
-        let clause_stronger_than = |clause1: &Vec<ConditionAtom>,
-                                    clause2: &Vec<ConditionAtom>|
-         -> bool {
-            clause1.iter().all(|atom1| {
-                clause2.iter().any(|atom2| atom1.is_stronger_than(atom2))
-            })
-        };
-
         let mut clauses = cond1.into_clauses();
         for next_clause in cond2.into_clauses() {
             // If the clause is weaker than current expression abort insertion
@@ -121,6 +122,31 @@ impl Condition {
 
         Condition(clauses)
     }
+
+    /// Check if this condition is always true when `other` is true.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dofus_stuff::dofapi::Condition;
+    ///
+    /// let none = Condition::new();
+    /// let ap: Condition = serde_json::from_str(r#"["AP < 12"]"#).unwrap();
+    /// let ap_mp: Condition =
+    ///     serde_json::from_str(r#"["AP < 12", "MP < 6"]"#).unwrap();
+    ///
+    /// assert!(none.is_implied_by(&ap));
+    /// assert!(ap.is_implied_by(&ap_mp));
+    /// assert!(!ap_mp.is_implied_by(&ap));
+    /// ```
+    pub fn is_implied_by(&self, other: &Self) -> bool {
+        self.clauses().iter().all(|clause| {
+            other
+                .clauses()
+                .iter()
+                .any(|other_clause| clause_stronger_than(other_clause, clause))
+        })
+    }
 }
 
 impl From<ConditionAtom> for Condition {
@@ -18,6 +18,20 @@ pub enum Element {
 }
 
 impl Element {
+    /// List of all elements, ordered by their index.
+    pub const ALL: [Element; 5] = [
+        Element::Earth,
+        Element::Water,
+        Element::Air,
+        Element::Fire,
+        Element::Neutral,
+    ];
+
+    /// Position of this element in `Element::ALL`.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
     /// Return the element that boosts the damages applied in this element.
     pub fn effective_stat(&self) -> Element {
         match self {
@@ -34,7 +48,7 @@ impl Element {
 // |____/ \__,_|_| |_| |_|\__,_|\__, |\___|_____|_|_| |_|\___|
 //                              |___/
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub enum Effect {
     Hit {
         element: Element,
@@ -43,9 +57,81 @@ pub enum Effect {
         #[serde(default)]
         lifesteal: bool,
     },
+    /// Damages in percent of the caster's life.
+    LifeHit {
+        element: Element,
+        bounds:  RangeInclusive<u8>,
+    },
+    Heal {
+        bounds: RangeInclusive<u8>,
+    },
+    /// Shield points in percent of the caster's level.
+    Shield {
+        bounds: RangeInclusive<u8>,
+    },
+    /// Damages of pushing a target back by some cells into an obstacle.
+    Pushback {
+        cells: u8,
+    },
+    /// Damages of a trap, boosted by trap power and trap damages.
+    Trap {
+        element: Element,
+        bounds:  RangeInclusive<u8>,
+    },
+}
+
+impl Effect {
+    /// List the effects obtained for each possible roll of this effect, all
+    /// of them being equally likely. Bounds may be given in any order.
+    pub fn each_roll(&self) -> Vec<Effect> {
+        let rolls = |bounds: &RangeInclusive<u8>| {
+            let (start, end) = (*bounds.start(), *bounds.end());
+            (start.min(end)..=start.max(end))
+                .map(|roll| roll..=roll)
+                .collect::<Vec<_>>()
+        };
+
+        match self {
+            Effect::Hit {
+                element,
+                bounds,
+                lifesteal,
+            } => rolls(bounds)
+                .into_iter()
+                .map(|bounds| Effect::Hit {
+                    element: *element,
+                    bounds,
+                    lifesteal: *lifesteal,
+                })
+                .collect(),
+            Effect::LifeHit { element, bounds } => rolls(bounds)
+                .into_iter()
+                .map(|bounds| Effect::LifeHit {
+                    element: *element,
+                    bounds,
+                })
+                .collect(),
+            Effect::Heal { bounds } => rolls(bounds)
+                .into_iter()
+                .map(|bounds| Effect::Heal { bounds })
+                .collect(),
+            Effect::Shield { bounds } => rolls(bounds)
+                .into_iter()
+                .map(|bounds| Effect::Shield { bounds })
+                .collect(),
+            Effect::Trap { element, bounds } => rolls(bounds)
+                .into_iter()
+                .map(|bounds| Effect::Trap {
+                    element: *element,
+                    bounds,
+                })
+                .collect(),
+            Effect::Pushback { .. } => vec![self.clone()],
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub struct SpellEffects {
     pub effect: Vec<Effect>,
     pub ranged: bool,
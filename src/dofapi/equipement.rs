@@ -65,6 +65,17 @@ const WEAPON_TYPES: &[ItemType] = &[
     ItemType::SoulStone,
 ];
 
+/// List of kinds of weapons that can't be used together with a shield.
+const TWO_HANDED_TYPES: &[ItemType] = &[
+    ItemType::Axe,
+    ItemType::Staff,
+    ItemType::Bow,
+    ItemType::Shovel,
+    ItemType::Hammer,
+    ItemType::Scythe,
+    ItemType::Pickaxe,
+];
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct Equipement {
     #[serde(rename = "type")]
@@ -103,8 +114,29 @@ impl Equipement {
     pub fn is_weapon(&self) -> bool {
         WEAPON_TYPES.contains(&self.item_type)
     }
+
+    /// Check wether this equipement is a weapon that requires both hands
+    pub fn is_two_handed(&self) -> bool {
+        TWO_HANDED_TYPES.contains(&self.item_type)
+    }
+
+    /// Check wether this equipement can only be equiped once
+    pub fn is_unique(&self) -> bool {
+        self.set_id.is_some()
+            || self.item_type == ItemType::Trophy
+            || self.item_type == ItemType::Dofus
+    }
 }
 
+/// Two equipements are the same if they share the same identifier.
+impl PartialEq for Equipement {
+    fn eq(&self, other: &Self) -> bool {
+        self._id == other._id
+    }
+}
+
+impl Eq for Equipement {}
+
 /// Fix trophy conditions as big trophy are not referenced with a condition to
 /// use them in the website.
 ///
@@ -124,7 +156,6 @@ pub fn fix_all_trophy(db: &mut [Equipement]) {
             // bonuses.
             let has_no_malus = item
                 .statistics
-                .as_map()
                 .iter()
                 .all(|(_, bounds)| *bounds.start() >= 0);
 
@@ -135,14 +166,12 @@ pub fn fix_all_trophy(db: &mut [Equipement]) {
                     .filter(|other| other.level == item.level)
                     .all(|other| {
                         item.statistics
-                            .as_map()
                             .iter()
                             .filter(|(_kind, bounds)| *bounds.start() >= 0)
                             .any(|(kind, _bounds)| {
                                 other
                                     .statistics
-                                    .as_map()
-                                    .get(kind)
+                                    .get(&kind)
                                     .map(|bounds| *bounds.end() < 0)
                                     .unwrap_or(true)
                             })
@@ -3,11 +3,18 @@ mod condition;
 mod effect;
 mod equipement;
 mod set;
+mod spell;
+mod target;
 
-pub use carac::{CaracKind, CaracLines};
+pub use carac::{
+    CaracKind, CaracLines, CaracVec, Influence, CARAC_KIND_COUNT,
+};
 pub use condition::{Condition, ConditionAtom};
 pub use effect::{Effect, Element, SpellEffects};
 pub use equipement::{Equipement, ItemType};
 pub use set::Set;
+pub use spell::{CastableSpell, Spell, SpellError, SpellLevel, SpellRef};
+pub use target::Target;
 
 pub use equipement::fix_all_trophy;
+pub use spell::load_spells;
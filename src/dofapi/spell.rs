@@ -0,0 +1,182 @@
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::dofapi::effect::SpellEffects;
+
+/// A spell of a breed, with the characteristics of each of its levels.
+///
+/// # Examples
+///
+/// ```
+/// use dofus_stuff::dofapi::{Spell, SpellError, SpellRef};
+///
+/// let spells: Vec<Spell> = serde_json::from_str(
+///     r#"[{
+///         "name": "Ambush",
+///         "breed": "Cra",
+///         "levels": [{
+///             "ap_cost": 4,
+///             "range": [1, 8],
+///             "ranged": true,
+///             "effect": [{"Hit": {"element": "Air", "bounds": [7, 9]}}]
+///         }, {
+///             "ap_cost": 4,
+///             "range": [1, 10],
+///             "casts_per_turn": 2,
+///             "ranged": true,
+///             "critical": 10,
+///             "effect": [{"Hit": {"element": "Air", "bounds": [10, 12]}}]
+///         }]
+///     }]"#,
+/// )
+/// .unwrap();
+///
+/// let spell_ref = SpellRef {
+///     spell: "Ambush".to_string(),
+///     level: 2,
+/// };
+///
+/// let level = spell_ref.find(&spells).unwrap();
+/// assert_eq!(level.ap_cost, 4);
+/// assert_eq!(level.casts_per_turn, Some(2));
+/// assert_eq!(level.effects.critical, 10);
+///
+/// let spell_ref = SpellRef {
+///     spell: "Ambush".to_string(),
+///     level: 3,
+/// };
+///
+/// assert_eq!(
+///     spell_ref.find(&spells).unwrap_err(),
+///     SpellError::UnknownLevel {
+///         spell: "Ambush".to_string(),
+///         level: 3,
+///     }
+/// );
+///
+/// let spell_ref = SpellRef {
+///     spell: "Unknown".to_string(),
+///     level: 1,
+/// };
+///
+/// assert_eq!(
+///     spell_ref.find(&spells).unwrap_err(),
+///     SpellError::UnknownSpell("Unknown".to_string())
+/// );
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Spell {
+    pub name:   String,
+    pub breed:  String,
+    pub levels: Vec<SpellLevel>,
+}
+
+impl Spell {
+    /// Get a level of the spell, levels are numbered from 1.
+    pub fn get_level(&self, level: u8) -> Option<&SpellLevel> {
+        usize::from(level)
+            .checked_sub(1)
+            .and_then(|index| self.levels.get(index))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SpellLevel {
+    pub ap_cost: u8,
+    pub range:   RangeInclusive<u8>,
+
+    /// Number of times the spell can be cast during a turn, unlimited if not
+    /// specified.
+    #[serde(default)]
+    pub casts_per_turn: Option<u8>,
+
+    #[serde(flatten)]
+    pub effects: SpellEffects,
+}
+
+/// Reference to a level of a spell in a spell database.
+#[derive(Debug, Deserialize)]
+pub struct SpellRef {
+    pub spell: String,
+    pub level: u8,
+}
+
+impl SpellRef {
+    pub fn find<'s>(
+        &self,
+        spells: &'s [Spell],
+    ) -> Result<&'s SpellLevel, SpellError> {
+        let spell = spells
+            .iter()
+            .find(|spell| spell.name == self.spell)
+            .ok_or_else(|| SpellError::UnknownSpell(self.spell.clone()))?;
+
+        spell
+            .get_level(self.level)
+            .ok_or_else(|| SpellError::UnknownLevel {
+                spell: self.spell.clone(),
+                level: self.level,
+            })
+    }
+}
+
+/// A spell that can be cast during a turn, either described inline or
+/// referenced from the spell database.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum CastableSpell {
+    Ref(SpellRef),
+    Spell(SpellLevel),
+}
+
+impl CastableSpell {
+    /// Get the spell level, looking it up in the database if it is a
+    /// reference.
+    pub fn resolve(self, spells: &[Spell]) -> Result<SpellLevel, SpellError> {
+        match self {
+            CastableSpell::Ref(spell_ref) => {
+                Ok(spell_ref.find(spells)?.clone())
+            }
+            CastableSpell::Spell(level) => Ok(level),
+        }
+    }
+}
+
+/// Read a spell database from a JSON file listing spells.
+///
+/// # Examples
+///
+/// ```
+/// use dofus_stuff::dofapi::load_spells;
+///
+/// let spells = load_spells("examples/spells.json").unwrap();
+/// assert!(spells.iter().any(|spell| spell.name == "Ambush"));
+/// ```
+pub fn load_spells(path: impl AsRef<Path>) -> io::Result<Vec<Spell>> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(io::BufReader::new(file))?)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum SpellError {
+    UnknownSpell(String),
+    UnknownLevel { spell: String, level: u8 },
+}
+
+impl fmt::Display for SpellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpellError::UnknownSpell(spell) => {
+                write!(f, "unknown spell `{}`", spell)
+            }
+            SpellError::UnknownLevel { spell, level } => {
+                write!(f, "spell `{}` has no level {}", spell, level)
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::dofapi::effect::Element;
+
+/// Defensive characteristics of a monster or of an opponent that damages are
+/// computed against.
+///
+/// # Examples
+///
+/// ```
+/// use dofus_stuff::dofapi::{Element, Target};
+///
+/// let target: Target = serde_json::from_str(
+///     r#"{
+///         "resistance": {"Air": 20},
+///         "per_resistance": {"Air": 30, "Fire": -10},
+///         "per_melee_resistance": 15
+///     }"#,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(target.get_resistance(Element::Air), 20);
+/// assert_eq!(target.get_per_resistance(Element::Fire), -10);
+/// assert_eq!(target.get_per_resistance(Element::Water), 0);
+/// assert_eq!(target.final_multiplier, 1.);
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub struct Target {
+    /// Fixed resistances for each element.
+    #[serde(default)]
+    pub resistance: HashMap<Element, i16>,
+
+    /// Percent resistances for each element.
+    #[serde(default)]
+    pub per_resistance: HashMap<Element, i16>,
+
+    #[serde(default)]
+    pub per_melee_resistance: i16,
+
+    #[serde(default)]
+    pub per_ranged_resistance: i16,
+
+    #[serde(default)]
+    pub critical_resistance: i16,
+
+    #[serde(default)]
+    pub pushback_resistance: i16,
+
+    /// Multiplier applied to the final damages, for example `1.2` for a
+    /// target which suffers 20% more damages.
+    #[serde(default = "default_multiplier")]
+    pub final_multiplier: f64,
+}
+
+impl Target {
+    pub fn get_resistance(&self, element: Element) -> i16 {
+        *self.resistance.get(&element).unwrap_or(&0)
+    }
+
+    pub fn get_per_resistance(&self, element: Element) -> i16 {
+        *self.per_resistance.get(&element).unwrap_or(&0)
+    }
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        Target {
+            resistance:            HashMap::new(),
+            per_resistance:        HashMap::new(),
+            per_melee_resistance:  0,
+            per_ranged_resistance: 0,
+            critical_resistance:   0,
+            pushback_resistance:   0,
+            final_multiplier:      default_multiplier(),
+        }
+    }
+}
+
+fn default_multiplier() -> f64 {
+    1.
+}
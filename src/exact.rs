@@ -0,0 +1,547 @@
+//! Exact search of the best items for additive targets.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::character::{
+    Character, CharacterError, RawCaracs, ValidationIssue,
+};
+use crate::dofapi::{CaracKind, CaracLines, Equipement, ItemType};
+
+/// Default maximal number of nodes explored by the solver.
+const DEFAULT_NODE_LIMIT: u64 = 10_000_000;
+
+/// Parameters of the branch and bound solver.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ExactConfig {
+    /// Weight of each characteristic in the maximized sum.
+    pub weights: Vec<(CaracKind, f64)>,
+
+    /// Maximal number of nodes explored before giving up on proving
+    /// optimality.
+    pub node_limit: u64,
+
+    /// Maximal duration of the search in seconds.
+    pub time_limit: Option<f64>,
+}
+
+impl Default for ExactConfig {
+    fn default() -> Self {
+        ExactConfig {
+            weights:    Vec::new(),
+            node_limit: DEFAULT_NODE_LIMIT,
+            time_limit: None,
+        }
+    }
+}
+
+/// Best build found by the solver, with a bound on the score of any build.
+#[derive(Debug)]
+pub struct ExactSolution<'i> {
+    pub character: Character<'i>,
+
+    /// Weighted sum of the characteristics of the build.
+    pub score: f64,
+
+    /// Upper bound on the score of any build.
+    pub bound: f64,
+
+    /// Whether `Character::validate` reports no issue for the build, apart
+    /// from unspent characteristic points which the solver doesn't change.
+    pub valid: bool,
+
+    /// Number of nodes explored by the solver.
+    pub nodes: u64,
+}
+
+impl ExactSolution<'_> {
+    /// Distance between the score of the build and the best possible score.
+    pub fn gap(&self) -> f64 {
+        (self.bound - self.score).max(0.)
+    }
+
+    /// Check if the build is proven to be the best valid build.
+    pub fn is_optimal(&self) -> bool {
+        self.valid && self.gap() < 1e-9
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ExactError<'i> {
+    /// The characteristic is capped or derived from other characteristics,
+    /// so it can't be weighted by the solver.
+    NotAdditive(CaracKind),
+    Character(CharacterError<'i>),
+}
+
+impl<'i> From<CharacterError<'i>> for ExactError<'i> {
+    fn from(err: CharacterError<'i>) -> Self {
+        ExactError::Character(err)
+    }
+}
+
+impl fmt::Display for ExactError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExactError::NotAdditive(kind) => write!(
+                f,
+                "{} is capped or derived from other characteristics, it \
+                 can't be weighted by the exact solver",
+                kind
+            ),
+            ExactError::Character(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// Check if a characteristic of a character is the sum of the lines of its
+/// items and set bonuses, up to a constant.
+///
+/// # Examples
+///
+/// ```
+/// use dofus_stuff::dofapi::{CaracKind::*, Element::*};
+/// use dofus_stuff::exact::is_additive;
+///
+/// assert!(is_additive(&Vitality));
+/// assert!(is_additive(&Stats(Air)));
+/// assert!(!is_additive(&AP));
+/// assert!(!is_additive(&Damage(Air)));
+/// ```
+pub fn is_additive(kind: &CaracKind) -> bool {
+    use CaracKind::*;
+
+    let is_capped = matches!(kind, AP | MP | Range | PerResistance(_));
+    !is_capped && RawCaracs::carac_sources(kind) == [kind.clone()]
+}
+
+/// Item that may be equiped in a slot.
+#[derive(Clone, Copy)]
+struct Candidate<'i> {
+    item:  &'i Equipement,
+    score: f64,
+    /// Index of the set of the item in `Solver::set_bonus`.
+    set:   Option<usize>,
+}
+
+/// Maximize the sum of characteristics weighted by `config.weights`, without
+/// changing locked slots and characteristic points of `init`. Only additive
+/// characteristics can be weighted, see `is_additive`.
+///
+/// Slots are branched on one after the other, and a branch is pruned when the
+/// best score of its remaining slots added to the best set bonuses reachable
+/// from the current count of items of each set can't improve the best build.
+/// There is no explicit branching on the number of items of each set, set
+/// bonuses only tighten the bounds.
+///
+/// Item conditions are not taken into account during the search: the build
+/// is only proven optimal if its conditions are met. If the search is stopped
+/// by a limit of `config`, the returned bound tells how far the build may be
+/// from the optimum.
+///
+/// # Examples
+///
+/// ```
+/// use dofus_stuff::character::Character;
+/// use dofus_stuff::dofapi::{CaracKind, Equipement, Set};
+/// use dofus_stuff::exact::{solve_additive, ExactConfig};
+/// # let parse_item = |item_type, id, vitality, set_id| -> Equipement {
+/// #     serde_json::from_str(&format!(
+/// #         r#"{{
+/// #             "type": "{}", "ankamaId": {}, "_id": {}, "name": "Item",
+/// #             "level": 1, "url": "", "imgUrl": "", "setId": {},
+/// #             "statistics": [{{"Vitality": {{"min": 1, "max": {}}}}}]
+/// #         }}"#,
+/// #         item_type, id, id, set_id, vitality
+/// #     ))
+/// #     .unwrap()
+/// # };
+/// # let set: Set = serde_json::from_str(
+/// #     r#"{
+/// #         "ankamaId": 1, "_id": 1, "name": "Set", "level": 1,
+/// #         "url": "", "imgUrl": "",
+/// #         "bonus": {"2": [{"Vitality": {"min": 100}}]}
+/// #     }"#,
+/// # )
+/// # .unwrap();
+///
+/// // Items of set 1 give less vitality, but wearing both of them gives a
+/// // bonus of 100 vitality
+/// let db = vec![
+///     parse_item("Hat", 1, 100, 0),
+///     parse_item("Hat", 2, 60, 1),
+///     parse_item("Cloak", 3, 100, 0),
+///     parse_item("Cloak", 4, 60, 1),
+/// ];
+/// let db_sets = vec![(1, set)].into_iter().collect();
+///
+/// let config = ExactConfig {
+///     weights: vec![(CaracKind::Vitality, 1.)],
+///     ..Default::default()
+/// };
+/// let solution =
+///     solve_additive(Character::new(200, &db_sets), &db, &config).unwrap();
+///
+/// let ids: Vec<_> = solution
+///     .character
+///     .iter_items()
+///     .map(|item| item._id)
+///     .collect();
+/// assert_eq!(ids, [2, 4]);
+///
+/// // Base vitality of the character is part of the score
+/// assert_eq!(solution.score, 1150. + 220.);
+/// assert!(solution.is_optimal());
+/// ```
+pub fn solve_additive<'i>(
+    init: Character<'i>,
+    db_equipements: &'i [Equipement],
+    config: &ExactConfig,
+) -> Result<ExactSolution<'i>, ExactError<'i>> {
+    let weights = &config.weights;
+
+    if let Some((kind, _)) =
+        weights.iter().find(|(kind, _)| !is_additive(kind))
+    {
+        return Err(ExactError::NotAdditive(kind.clone()));
+    }
+
+    let lines_score = |lines: &CaracLines| -> f64 {
+        weights
+            .iter()
+            .map(|(kind, weight)| weight * f64::from(lines.values().get(kind)))
+            .sum()
+    };
+
+    // Score of the bonus of each set for each count of items
+    let mut set_index = HashMap::new();
+    let mut set_bonus: Vec<Vec<f64>> = Vec::new();
+
+    let mut get_set = |set_id: Option<u64>| -> Option<usize> {
+        let set = init.get_sets().get(&set_id?)?;
+        let index = *set_index.entry(set._id).or_insert_with(|| {
+            let max_count = set.bonus.keys().copied().max().unwrap_or(0);
+            set_bonus.push(
+                (0..=max_count)
+                    .map(|count| set.bonus.get(&count).map_or(0., lines_score))
+                    .collect(),
+            );
+            set_bonus.len() - 1
+        });
+        Some(index)
+    };
+
+    // Locked items are part of every build
+    let mut locked = State::default();
+
+    for item in init
+        .item_slots
+        .iter()
+        .filter(|slot| slot.is_locked())
+        .filter_map(|slot| slot.get_item())
+    {
+        let candidate = Candidate {
+            item,
+            score: lines_score(&item.statistics),
+            set: get_set(item.set_id),
+        };
+        locked.push(&candidate);
+    }
+
+    let free_slots: Vec<usize> = (0..init.item_slots.len())
+        .filter(|&slot_i| !init.item_slots[slot_i].is_locked())
+        .collect();
+
+    let slots: Vec<Vec<Candidate>> = free_slots
+        .iter()
+        .map(|&slot_i| {
+            let allowed = init.item_slots[slot_i].get_allowed();
+
+            // Items that can be equiped in several slots must be kept for
+            // each of them, in case unique items are already used by others
+            let keep = free_slots
+                .iter()
+                .filter(|&&other| {
+                    init.item_slots[other].get_allowed() == allowed
+                })
+                .count();
+
+            let mut candidates: Vec<_> = db_equipements
+                .iter()
+                .filter(|item| allowed.contains(&item.item_type))
+                .filter(|item| item.level <= init.get_level())
+                .map(|item| Candidate {
+                    item,
+                    score: lines_score(&item.statistics),
+                    set: get_set(item.set_id),
+                })
+                .collect();
+
+            candidates.sort_by(|x, y| {
+                y.score
+                    .partial_cmp(&x.score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+
+            // Items of the same set that raise the same conflicts are
+            // interchangeable, only the best ones are kept
+            let mut kept = HashMap::new();
+            candidates.retain(|candidate| {
+                let useful = candidate.score > 0. || candidate.set.is_some();
+                let group = (
+                    candidate.set,
+                    candidate.item.is_two_handed(),
+                    candidate.item.item_type == ItemType::Shield,
+                );
+                let count = kept.entry(group).or_insert(0);
+                *count += 1;
+                useful && *count <= keep
+            });
+
+            candidates
+        })
+        .collect();
+
+    // Bounds on the score of remaining slots
+    let mut best_suffix = vec![0.; slots.len() + 1];
+    let mut set_suffix = vec![vec![0; set_bonus.len()]; slots.len() + 1];
+
+    for depth in (0..slots.len()).rev() {
+        let best = slots[depth].first().map_or(0., |c| c.score.max(0.));
+        best_suffix[depth] = best_suffix[depth + 1] + best;
+        set_suffix[depth] = set_suffix[depth + 1].clone();
+
+        let mut sets: Vec<_> =
+            slots[depth].iter().filter_map(|c| c.set).collect();
+        sets.sort_unstable();
+        sets.dedup();
+
+        for set in sets {
+            set_suffix[depth][set] += 1;
+        }
+    }
+
+    locked.set_counts.resize(set_bonus.len(), 0);
+
+    let mut solver = Solver {
+        slots,
+        set_bonus,
+        best_suffix,
+        set_suffix,
+        state: locked,
+        best: (f64::NEG_INFINITY, Vec::new()),
+        open_bound: f64::NEG_INFINITY,
+        stopped: false,
+        nodes: 0,
+        node_limit: config.node_limit,
+        deadline: config
+            .time_limit
+            .map(|limit| Instant::now() + Duration::from_secs_f64(limit)),
+    };
+
+    // The build without any other item is always valid
+    solver.best = (solver.value(), Vec::new());
+    solver.explore(0);
+
+    let mut character = init;
+
+    for &slot_i in &free_slots {
+        if character.item_slots[slot_i].get_item().is_some() {
+            character.unequip(slot_i)?;
+        }
+    }
+
+    for (&slot_i, item) in free_slots.iter().zip(&solver.best.1) {
+        if let Some(item) = item {
+            character.equip(slot_i, item)?;
+        }
+    }
+
+    // The solver ignores constant parts of characteristics, such as base
+    // values and characteristic points
+    let caracs = character.get_caracs();
+    let score: f64 = weights
+        .iter()
+        .map(|(kind, weight)| weight * f64::from(caracs.get_carac(kind)))
+        .sum();
+    let offset = score - solver.best.0;
+
+    Ok(ExactSolution {
+        score,
+        bound: solver.best.0.max(solver.open_bound) + offset,
+        valid: character
+            .validate()
+            .issues
+            .iter()
+            .all(|issue| matches!(issue, ValidationIssue::UnspentPoints(_))),
+        character,
+        nodes: solver.nodes,
+    })
+}
+
+/// Items chosen in a branch of the search.
+#[derive(Default)]
+struct State<'i> {
+    chosen:         Vec<Option<&'i Equipement>>,
+    score:          f64,
+    set_counts:     Vec<u8>,
+    unique_ids:     Vec<u64>,
+    has_two_handed: bool,
+    has_shield:     bool,
+}
+
+impl<'i> State<'i> {
+    fn accepts(&self, candidate: &Candidate) -> bool {
+        let item = candidate.item;
+        let is_duplicate =
+            item.is_unique() && self.unique_ids.contains(&item._id);
+        let is_conflict = (item.is_two_handed() && self.has_shield)
+            || (item.item_type == ItemType::Shield && self.has_two_handed);
+        !is_duplicate && !is_conflict
+    }
+
+    fn push(&mut self, candidate: &Candidate<'i>) {
+        let item = candidate.item;
+        self.score += candidate.score;
+
+        if let Some(set) = candidate.set {
+            if self.set_counts.len() <= set {
+                self.set_counts.resize(set + 1, 0);
+            }
+
+            self.set_counts[set] += 1;
+        }
+
+        if item.is_unique() {
+            self.unique_ids.push(item._id);
+        }
+
+        self.has_two_handed |= item.is_two_handed();
+        self.has_shield |= item.item_type == ItemType::Shield;
+    }
+}
+
+struct Solver<'i> {
+    /// Candidates of each free slot, sorted by decreasing score.
+    slots:       Vec<Vec<Candidate<'i>>>,
+    set_bonus:   Vec<Vec<f64>>,
+    /// Sum of the best scores of the slots from a given depth.
+    best_suffix: Vec<f64>,
+    /// Number of slots from a given depth accepting items of each set.
+    set_suffix:  Vec<Vec<u8>>,
+
+    state:      State<'i>,
+    best:       (f64, Vec<Option<&'i Equipement>>),
+    /// Greatest bound of the branches that were not explored.
+    open_bound: f64,
+    /// Set when a limit of the search is reached.
+    stopped:    bool,
+    nodes:      u64,
+    node_limit: u64,
+    deadline:   Option<Instant>,
+}
+
+impl<'i> Solver<'i> {
+    fn bonus(&self, set: usize, count: u8) -> f64 {
+        *self.set_bonus[set].get(usize::from(count)).unwrap_or(&0.)
+    }
+
+    /// Score of the current branch.
+    fn value(&self) -> f64 {
+        let bonuses: f64 = (0..self.set_bonus.len())
+            .map(|set| self.bonus(set, self.state.set_counts[set]))
+            .sum();
+        self.state.score + bonuses
+    }
+
+    /// Upper bound on the score of builds of the current branch.
+    fn bound(&self, depth: usize) -> f64 {
+        let bonuses: f64 = (0..self.set_bonus.len())
+            .map(|set| {
+                let count = self.state.set_counts[set];
+                (count..=count + self.set_suffix[depth][set])
+                    .map(|count| self.bonus(set, count))
+                    .fold(f64::NEG_INFINITY, f64::max)
+            })
+            .sum();
+        self.state.score + self.best_suffix[depth] + bonuses
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.nodes >= self.node_limit
+            || (self.nodes.is_multiple_of(1024)
+                && self.deadline.is_some_and(|d| Instant::now() >= d))
+    }
+
+    fn explore(&mut self, depth: usize) {
+        self.nodes += 1;
+
+        if depth == self.slots.len() {
+            let value = self.value();
+
+            if value > self.best.0 {
+                self.best = (value, self.state.chosen.clone());
+            }
+
+            return;
+        }
+
+        let bound = self.bound(depth);
+
+        if bound <= self.best.0 + 1e-9 {
+            return;
+        }
+
+        self.stopped |= self.is_exhausted();
+
+        for index in 0..self.slots[depth].len() {
+            let candidate = self.slots[depth][index];
+
+            // Once stopped, the bound of this node covers all of its
+            // remaining branches
+            if self.stopped {
+                self.open_bound = self.open_bound.max(bound);
+                return;
+            }
+
+            if !self.state.accepts(&candidate) {
+                continue;
+            }
+
+            let saved = (
+                self.state.score,
+                self.state.unique_ids.len(),
+                self.state.has_two_handed,
+                self.state.has_shield,
+            );
+
+            self.state.push(&candidate);
+            self.state.chosen.push(Some(candidate.item));
+            self.explore(depth + 1);
+            self.state.chosen.pop();
+
+            if let Some(set) = candidate.set {
+                self.state.set_counts[set] -= 1;
+            }
+
+            self.state.score = saved.0;
+            self.state.unique_ids.truncate(saved.1);
+            self.state.has_two_handed = saved.2;
+            self.state.has_shield = saved.3;
+        }
+
+        if self.stopped {
+            self.open_bound = self.open_bound.max(bound);
+            return;
+        }
+
+        // Leave the slot empty
+        self.state.chosen.push(None);
+        self.explore(depth + 1);
+        self.state.chosen.pop();
+    }
+}
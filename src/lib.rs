@@ -2,7 +2,8 @@
 extern crate lazy_static;
 
 pub mod character;
+pub mod distribution;
 pub mod dofapi;
+pub mod exact;
+pub mod optimizer;
 pub mod search;
-
-mod rls;
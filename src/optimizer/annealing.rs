@@ -0,0 +1,207 @@
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use serde::Deserialize;
+
+use super::{refine, Optimizer, Progress, SearchSpace};
+
+/// Default number of steps of a search.
+const DEFAULT_STEPS: u32 = 100_000;
+
+/// Parameters of the simulated annealing.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct AnnealingConfig {
+    /// Maximal number of steps of a search.
+    pub steps: u32,
+
+    /// Maximal duration of a search in seconds, the schedule then follows
+    /// whichever of steps or time runs out first. Searches stopped by time
+    /// can't be replayed from their seed.
+    pub time_limit: Option<f64>,
+
+    pub schedule:   Schedule,
+    pub acceptance: Acceptance,
+
+    /// Stop the search if the best evaluation didn't improve for this number
+    /// of steps.
+    pub stagnation: Option<u32>,
+}
+
+impl Default for AnnealingConfig {
+    fn default() -> Self {
+        AnnealingConfig {
+            steps:      DEFAULT_STEPS,
+            time_limit: None,
+            schedule:   Schedule::Geometric,
+            acceptance: Acceptance::Relative,
+            stagnation: None,
+        }
+    }
+}
+
+/// Evolution of the temperature over the search, the temperature starts at 1
+/// and decreases towards 0.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub enum Schedule {
+    /// Temperature decreases exponentially, reaching 1e-3 after 75% of the
+    /// search.
+    Geometric,
+    /// Temperature decreases linearly, reaching 0 at the end of the search.
+    Linear,
+    /// Temperature follows the logarithm of the remaining part of the
+    /// search, which keeps it high for longer before it drops to 0 at the
+    /// end.
+    Logarithmic,
+    /// Geometric schedule restarted some number of times.
+    Reheating { cycles: u32 },
+}
+
+impl Schedule {
+    /// Temperature after given portion of the search, between 0 and 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dofus_stuff::search::Schedule;
+    ///
+    /// assert_eq!(Schedule::Geometric.temperature(0.), 1.);
+    /// assert!((Schedule::Geometric.temperature(0.75) - 1e-3).abs() < 1e-9);
+    /// assert_eq!(Schedule::Linear.temperature(0.25), 0.75);
+    /// assert!(Schedule::Logarithmic.temperature(0.5) > 0.85);
+    /// assert_eq!(Schedule::Logarithmic.temperature(1.), 0.);
+    ///
+    /// let reheating = Schedule::Reheating { cycles: 2 };
+    /// assert_eq!(reheating.temperature(0.5), 1.);
+    /// ```
+    pub fn temperature(self, progress: f64) -> f64 {
+        let geometric = |progress: f64| 1e-3f64.powf(progress / 0.75);
+
+        match self {
+            Schedule::Geometric => geometric(progress),
+            Schedule::Linear => (1. - progress).max(0.),
+            Schedule::Logarithmic => {
+                let remaining = (1. - progress).max(0.);
+                (1. + 1e3 * remaining).ln() / (1. + 1e3f64).ln()
+            }
+            Schedule::Reheating { cycles } => {
+                geometric((progress * f64::from(cycles.max(1))).fract())
+            }
+        }
+    }
+}
+
+/// Rule deciding if a move from the current value to a new one is accepted.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub enum Acceptance {
+    /// Accept moves that don't decrease the evaluation by a factor greater
+    /// than the temperature.
+    Relative,
+    /// Accept moves decreasing the logarithm of the evaluation by `delta`
+    /// with probability `exp(-delta / temperature)`.
+    Metropolis,
+}
+
+impl Acceptance {
+    fn accept(
+        self,
+        curr_eval: f64,
+        new_eval: f64,
+        temperature: f64,
+        rng: &mut impl rand::Rng,
+    ) -> bool {
+        match self {
+            Acceptance::Relative => new_eval > (1. - temperature) * curr_eval,
+            Acceptance::Metropolis => {
+                if new_eval >= curr_eval {
+                    return true;
+                }
+
+                let delta = new_eval.ln() - curr_eval.ln();
+                temperature > 0.
+                    && rng.gen::<f64>() < (delta / temperature).exp()
+            }
+        }
+    }
+}
+
+impl Optimizer for AnnealingConfig {
+    fn optimize<S, R, O>(
+        &self,
+        space: &S,
+        rng: &mut R,
+        mut observe: O,
+    ) -> S::Point
+    where
+        S: SearchSpace,
+        R: Rng,
+        O: FnMut(&Progress) -> bool,
+    {
+        let start = Instant::now();
+        let time_limit = self.time_limit.map(Duration::from_secs_f64);
+        let steps = self.steps.max(1);
+
+        let mut curr = space.init();
+        let mut curr_eval = space.eval(&curr);
+        let mut best = curr.clone();
+        let mut best_eval = curr_eval;
+        let mut last_improvement = 0;
+
+        for step in 1..=steps {
+            // Progress of the search, according to the step count or the
+            // elapsed time
+            let mut progress = f64::from(step) / f64::from(steps);
+
+            if let Some(time_limit) = time_limit {
+                let elapsed = start.elapsed();
+
+                if elapsed >= time_limit {
+                    break;
+                }
+
+                progress = progress
+                    .max(elapsed.as_secs_f64() / time_limit.as_secs_f64());
+            }
+
+            let temperature = self.schedule.temperature(progress);
+            let new = space.walk(curr.clone(), rng);
+            let new_eval = space.eval(&new);
+
+            let accepted =
+                self.acceptance
+                    .accept(curr_eval, new_eval, temperature, rng);
+            space.record_move(accepted);
+
+            if accepted {
+                curr = new;
+                curr_eval = new_eval;
+            }
+
+            refine(space, step, &mut curr, &mut curr_eval);
+
+            if curr_eval > best_eval {
+                best = curr.clone();
+                best_eval = curr_eval;
+                last_improvement = step;
+            } else if let Some(stagnation) = self.stagnation {
+                if step - last_improvement >= stagnation {
+                    break;
+                }
+            }
+
+            let progress = Progress {
+                step,
+                steps,
+                temperature,
+                current_eval: curr_eval,
+                best_eval,
+            };
+
+            if !observe(&progress) {
+                break;
+            }
+        }
+
+        best
+    }
+}
@@ -0,0 +1,149 @@
+use std::cmp::Ordering;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
+
+use super::{refine, Optimizer, Progress, SearchSpace};
+
+/// Parameters of the genetic algorithm.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct GeneticConfig {
+    /// Number of individuals of each generation.
+    pub population: usize,
+
+    pub generations: u32,
+
+    /// Number of random individuals competing to be selected as a parent.
+    pub tournament: usize,
+
+    /// Probability for a child to be mutated by a random move.
+    pub mutation: f64,
+
+    /// Number of best individuals copied unchanged into the next generation.
+    pub elitism: usize,
+}
+
+impl Default for GeneticConfig {
+    fn default() -> Self {
+        GeneticConfig {
+            population:  50,
+            generations: 2_000,
+            tournament:  3,
+            mutation:    0.5,
+            elitism:     2,
+        }
+    }
+}
+
+fn cmp_eval<T>((_, x): &(T, f64), (_, y): &(T, f64)) -> Ordering {
+    x.partial_cmp(y).unwrap_or(Ordering::Equal)
+}
+
+impl GeneticConfig {
+    /// Pick the best of `self.tournament` random individuals.
+    fn select<'p, T>(
+        &self,
+        population: &'p [(T, f64)],
+        rng: &mut impl Rng,
+    ) -> &'p (T, f64) {
+        (0..self.tournament.max(1))
+            .map(|_| population.choose(rng).unwrap())
+            .max_by(|x, y| cmp_eval(x, y))
+            .unwrap()
+    }
+}
+
+impl Optimizer for GeneticConfig {
+    fn optimize<S, R, O>(
+        &self,
+        space: &S,
+        rng: &mut R,
+        mut observe: O,
+    ) -> S::Point
+    where
+        S: SearchSpace,
+        R: Rng,
+        O: FnMut(&Progress) -> bool,
+    {
+        let size = self.population.max(2);
+        let generations = self.generations.max(1);
+        let mutation = self.mutation.clamp(0., 1.);
+
+        let init = space.init();
+        let init_eval = space.eval(&init);
+        let mut population = Vec::with_capacity(size);
+        population.push((init, init_eval));
+
+        // The initial population spreads from the starting point by random
+        // moves from already generated individuals
+        while population.len() < size {
+            let parent = population.choose(rng).unwrap().0.clone();
+            let point = space.walk(parent, rng);
+            let eval = space.eval(&point);
+            population.push((point, eval));
+        }
+
+        let mut best = population
+            .iter()
+            .max_by(|x, y| cmp_eval(x, y))
+            .unwrap()
+            .clone();
+
+        for generation in 1..=generations {
+            population.sort_by(|x, y| cmp_eval(y, x));
+            let mut next: Vec<_> =
+                population.iter().take(self.elitism).cloned().collect();
+
+            while next.len() < size {
+                let (father, father_eval) = self.select(&population, rng);
+                let (mother, _) = self.select(&population, rng);
+                let mut child = space.crossover(father, mother, rng);
+                let mutated = rng.gen_bool(mutation);
+
+                if mutated {
+                    child = space.walk(child, rng);
+                }
+
+                let eval = space.eval(&child);
+
+                // Mutations are considered successful if they don't produce
+                // a child worse than its first parent
+                if mutated {
+                    space.record_move(eval >= *father_eval);
+                }
+
+                next.push((child, eval));
+            }
+
+            population = next;
+            let generation_best =
+                population.iter_mut().max_by(|x, y| cmp_eval(x, y)).unwrap();
+            refine(
+                space,
+                generation,
+                &mut generation_best.0,
+                &mut generation_best.1,
+            );
+
+            if generation_best.1 > best.1 {
+                best = generation_best.clone();
+            }
+
+            let progress = Progress {
+                step:         generation,
+                steps:        generations,
+                temperature:  0.,
+                current_eval: generation_best.1,
+                best_eval:    best.1,
+            };
+
+            if !observe(&progress) {
+                break;
+            }
+        }
+
+        best.0
+    }
+}
@@ -0,0 +1,201 @@
+//! Generic optimization strategies, used to search for builds.
+
+mod annealing;
+mod genetic;
+mod tabu;
+
+use rand::Rng;
+use serde::Deserialize;
+
+pub use annealing::{Acceptance, AnnealingConfig, Schedule};
+pub use genetic::GeneticConfig;
+pub use tabu::TabuConfig;
+
+/// A space of values that an optimizer can explore, looking for the value
+/// with the greatest evaluation.
+pub trait SearchSpace {
+    type Point: Clone;
+
+    /// Starting point of the search.
+    fn init(&self) -> Self::Point;
+
+    fn eval(&self, point: &Self::Point) -> f64;
+
+    /// Random move from a point to one of its neighbours.
+    fn walk<R: Rng>(&self, point: Self::Point, rng: &mut R) -> Self::Point;
+
+    /// Random combination of two points, returns the first one by default.
+    fn crossover<R: Rng>(
+        &self,
+        a: &Self::Point,
+        _b: &Self::Point,
+        _rng: &mut R,
+    ) -> Self::Point {
+        a.clone()
+    }
+
+    /// Identifier of a point, equal points must share the same key.
+    fn key(&self, point: &Self::Point) -> u64;
+
+    /// Called by optimizers after a point returned by `walk` was evaluated,
+    /// telling if the move was kept.
+    fn record_move(&self, _accepted: bool) {}
+
+    /// Improvement of the current point of an optimizer at given step, which
+    /// replaces it if it is evaluated higher. Unlike `walk`, this is not a
+    /// random move and it is not recorded.
+    fn refine(&self, _point: &Self::Point, _step: u32) -> Option<Self::Point> {
+        None
+    }
+}
+
+/// Replace a point with its refinement at given step if it is better.
+fn refine<S: SearchSpace>(
+    space: &S,
+    step: u32,
+    point: &mut S::Point,
+    eval: &mut f64,
+) {
+    if let Some(refined) = space.refine(point, step) {
+        let refined_eval = space.eval(&refined);
+
+        if refined_eval > *eval {
+            *point = refined;
+            *eval = refined_eval;
+        }
+    }
+}
+
+/// State of a search after a step.
+#[derive(Clone, Copy, Debug)]
+pub struct Progress {
+    pub step:  u32,
+    pub steps: u32,
+
+    /// Temperature of the annealing, always 0 for other optimizers.
+    pub temperature: f64,
+
+    pub current_eval: f64,
+    pub best_eval:    f64,
+}
+
+/// A strategy to search for the best point of a space.
+pub trait Optimizer {
+    /// Search for a point with a great evaluation and return the best point
+    /// met, `observe` is called after each step and the search stops early
+    /// if it returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dofus_stuff::optimizer::*;
+    /// use rand::{Rng, SeedableRng};
+    /// use rand_chacha::ChaCha8Rng;
+    ///
+    /// // Each move leads to a worse point than the starting one
+    /// struct Descent;
+    ///
+    /// impl SearchSpace for Descent {
+    ///     type Point = u32;
+    ///
+    ///     fn init(&self) -> u32 {
+    ///         0
+    ///     }
+    ///
+    ///     fn eval(&self, point: &u32) -> f64 {
+    ///         1. / (1. + f64::from(*point))
+    ///     }
+    ///
+    ///     fn walk<R: Rng>(&self, point: u32, _rng: &mut R) -> u32 {
+    ///         point + 1
+    ///     }
+    ///
+    ///     fn key(&self, point: &u32) -> u64 {
+    ///         u64::from(*point)
+    ///     }
+    /// }
+    ///
+    /// // The population of the genetic algorithm always keeps its best points
+    /// let genetic = GeneticConfig {
+    ///     generations: 100,
+    ///     ..Default::default()
+    /// };
+    /// let mut rng = ChaCha8Rng::seed_from_u64(0);
+    /// assert_eq!(genetic.optimize(&Descent, &mut rng, |_| true), 0);
+    ///
+    /// // Other optimizers move away from the best point but still return it
+    /// let annealing = |acceptance| {
+    ///     OptimizerConfig::Annealing(AnnealingConfig {
+    ///         steps: 1_000,
+    ///         acceptance,
+    ///         ..Default::default()
+    ///     })
+    /// };
+    /// let optimizers = [
+    ///     annealing(Acceptance::Relative),
+    ///     annealing(Acceptance::Metropolis),
+    ///     OptimizerConfig::Tabu(TabuConfig {
+    ///         steps: 100,
+    ///         ..Default::default()
+    ///     }),
+    /// ];
+    ///
+    /// for optimizer in &optimizers {
+    ///     let mut rng = ChaCha8Rng::seed_from_u64(0);
+    ///     let mut moved_away = false;
+    ///
+    ///     let point = optimizer.optimize(&Descent, &mut rng, |progress| {
+    ///         moved_away |= progress.current_eval < progress.best_eval;
+    ///         true
+    ///     });
+    ///
+    ///     assert!(moved_away);
+    ///     assert_eq!(point, 0);
+    /// }
+    /// ```
+    fn optimize<S, R, O>(
+        &self,
+        space: &S,
+        rng: &mut R,
+        observe: O,
+    ) -> S::Point
+    where
+        S: SearchSpace,
+        R: Rng,
+        O: FnMut(&Progress) -> bool;
+}
+
+/// Optimizer selected from the input.
+#[derive(Clone, Debug, Deserialize)]
+pub enum OptimizerConfig {
+    Annealing(AnnealingConfig),
+    Genetic(GeneticConfig),
+    Tabu(TabuConfig),
+}
+
+impl Default for OptimizerConfig {
+    fn default() -> Self {
+        OptimizerConfig::Annealing(AnnealingConfig::default())
+    }
+}
+
+impl Optimizer for OptimizerConfig {
+    fn optimize<S, R, O>(&self, space: &S, rng: &mut R, observe: O) -> S::Point
+    where
+        S: SearchSpace,
+        R: Rng,
+        O: FnMut(&Progress) -> bool,
+    {
+        match self {
+            OptimizerConfig::Annealing(config) => {
+                config.optimize(space, rng, observe)
+            }
+            OptimizerConfig::Genetic(config) => {
+                config.optimize(space, rng, observe)
+            }
+            OptimizerConfig::Tabu(config) => {
+                config.optimize(space, rng, observe)
+            }
+        }
+    }
+}
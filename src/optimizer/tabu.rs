@@ -0,0 +1,112 @@
+use std::collections::{HashSet, VecDeque};
+
+use rand::Rng;
+use serde::Deserialize;
+
+use super::{refine, Optimizer, Progress, SearchSpace};
+
+/// Parameters of the tabu search.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct TabuConfig {
+    pub steps: u32,
+
+    /// Number of random neighbours compared at each step.
+    pub neighbours: usize,
+
+    /// Number of steps during which a visited point can't be visited again.
+    pub tenure: usize,
+}
+
+impl Default for TabuConfig {
+    fn default() -> Self {
+        TabuConfig {
+            steps:      10_000,
+            neighbours: 10,
+            tenure:     100,
+        }
+    }
+}
+
+impl Optimizer for TabuConfig {
+    fn optimize<S, R, O>(
+        &self,
+        space: &S,
+        rng: &mut R,
+        mut observe: O,
+    ) -> S::Point
+    where
+        S: SearchSpace,
+        R: Rng,
+        O: FnMut(&Progress) -> bool,
+    {
+        let steps = self.steps.max(1);
+        let mut curr = space.init();
+        let mut curr_eval = space.eval(&curr);
+        let mut best = (curr.clone(), curr_eval);
+
+        let mut tabu_order = VecDeque::with_capacity(self.tenure + 1);
+        let mut tabu = HashSet::with_capacity(self.tenure + 1);
+        tabu_order.push_back(space.key(&curr));
+        tabu.insert(space.key(&curr));
+
+        for step in 1..=steps {
+            let mut chosen: Option<(S::Point, f64)> = None;
+
+            for _ in 0..self.neighbours.max(1) {
+                let new = space.walk(curr.clone(), rng);
+                let new_eval = space.eval(&new);
+                space.record_move(new_eval > curr_eval);
+
+                // Tabu points are only allowed if they improve the best point
+                // found so far
+                if new_eval <= best.1 && tabu.contains(&space.key(&new)) {
+                    continue;
+                }
+
+                match &chosen {
+                    Some((_, chosen_eval)) if *chosen_eval >= new_eval => {}
+                    _ => chosen = Some((new, new_eval)),
+                }
+            }
+
+            // The best allowed neighbour is always moved to, even if it is
+            // worse than the current point
+            if let Some((new, new_eval)) = chosen {
+                let key = space.key(&new);
+
+                if tabu.insert(key) {
+                    tabu_order.push_back(key);
+                }
+
+                while tabu_order.len() > self.tenure {
+                    let old = tabu_order.pop_front().unwrap();
+                    tabu.remove(&old);
+                }
+
+                curr = new;
+                curr_eval = new_eval;
+            }
+
+            refine(space, step, &mut curr, &mut curr_eval);
+
+            if curr_eval > best.1 {
+                best = (curr.clone(), curr_eval);
+            }
+
+            let progress = Progress {
+                step,
+                steps,
+                temperature: 0.,
+                current_eval: curr_eval,
+                best_eval: best.1,
+            };
+
+            if !observe(&progress) {
+                break;
+            }
+        }
+
+        best.0
+    }
+}
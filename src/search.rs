@@ -1,10 +1,28 @@
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
 use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
 
-use crate::character::{Character, RawCaracsValue};
-use crate::dofapi::{CaracKind, Element, Equipement};
-use crate::rls::rls;
+use crate::character::{
+    merge_influences, Character, CharacterError, RawCaracs, RawCaracsValue,
+};
+use crate::dofapi::{
+    CaracKind, CastableSpell, ConditionAtom, Element, Equipement, Influence,
+    ItemType, Spell, SpellError, SpellLevel,
+};
+pub use crate::optimizer::{
+    Acceptance, AnnealingConfig, GeneticConfig, Optimizer, OptimizerConfig,
+    Progress, Schedule, SearchSpace, TabuConfig,
+};
 
-const STEPS: u32 = 100_000;
 const ASSIGNABLE_CARACS: &[CaracKind] = &[
     CaracKind::Vitality,
     CaracKind::Wisdom,
@@ -14,39 +32,485 @@ const ASSIGNABLE_CARACS: &[CaracKind] = &[
     CaracKind::Stats(Element::Water),
 ];
 
+/// A requirement that must be met by the output build, spells are given as
+/// `S` just like in `RawCaracsValue`.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// use dofus_stuff::character::Character;
+/// use dofus_stuff::search::Constraint;
+///
+/// let db_sets = HashMap::new();
+/// let caracs = Character::new(200, &db_sets).get_caracs();
+///
+/// let ap: Constraint =
+///     serde_json::from_str(r#"[{"Carac": "AP"}, ">=", 11]"#).unwrap();
+/// let mp: Constraint =
+///     serde_json::from_str(r#"[{"Carac": "MP"}, "==", 4]"#).unwrap();
+///
+/// assert_eq!(ap.violation(&caracs), 3.);
+/// assert!(!ap.is_satisfied(&caracs));
+/// assert!(mp.is_satisfied(&caracs));
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Constraint<S = SpellLevel> {
+    pub value: RawCaracsValue<S>,
+    pub op:    Operator,
+    pub bound: f64,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+pub enum Operator {
+    #[serde(rename = ">=")]
+    AtLeast,
+    #[serde(rename = "<=")]
+    AtMost,
+    #[serde(rename = "==")]
+    Equal,
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operator::AtLeast => write!(f, ">="),
+            Operator::AtMost => write!(f, "<="),
+            Operator::Equal => write!(f, "=="),
+        }
+    }
+}
+
+impl Constraint {
+    /// Distance between the value of the character and the closest value
+    /// satisfying the constraint.
+    pub fn violation(&self, caracs: &RawCaracs) -> f64 {
+        let value = caracs.eval(&self.value);
+
+        match self.op {
+            Operator::AtLeast => (self.bound - value).max(0.),
+            Operator::AtMost => (value - self.bound).max(0.),
+            Operator::Equal => (value - self.bound).abs(),
+        }
+    }
+
+    pub fn is_satisfied(&self, caracs: &RawCaracs) -> bool {
+        self.violation(caracs) < 1e-9
+    }
+}
+
+impl Constraint<CastableSpell> {
+    /// Replace references to the spell database with the corresponding
+    /// spells, see `RawCaracsValue::resolve_spells`.
+    pub fn resolve_spells(
+        self,
+        spells: &[Spell],
+    ) -> Result<Constraint, SpellError> {
+        Ok(Constraint {
+            value: self.value.resolve_spells(spells)?,
+            op:    self.op,
+            bound: self.bound,
+        })
+    }
+}
+
+/// Equip an item, replacing the shield or the two-handed weapon it conflicts
+/// with.
+fn equip_replacing<'i>(
+    character: &mut Character<'i>,
+    slot_i: usize,
+    item: &'i Equipement,
+) -> Result<(), CharacterError<'i>> {
+    match character.equip(slot_i, item) {
+        Err(CharacterError::TwoHandedConflict(_, other)) => {
+            let other_i = character
+                .item_slots
+                .iter()
+                .position(|slot| slot.get_item() == Some(other))
+                .unwrap();
+            character.unequip(other_i)?;
+            character.equip(slot_i, item)
+        }
+        res => res,
+    }
+}
+
+/// Number of random items compared when looking for an item similar to
+/// another one.
+const SIMILAR_SAMPLES: usize = 8;
+
+/// Kinds of random moves applied to a build by `walk_character`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Move {
+    /// Replace a random item.
+    Item,
+    /// Move a few points from a characteristic to another.
+    Points,
+    /// Equip several items of the same set.
+    SetItems,
+    /// Replace the items of two interchangeable slots, like rings or Dofus.
+    SlotPair,
+    /// Replace an item with an item sharing most of its characteristics.
+    SimilarItem,
+    /// Move points to satisfy a violated condition of the items.
+    FixCondition,
+}
+
+impl Move {
+    const ALL: [Move; 6] = [
+        Move::Item,
+        Move::Points,
+        Move::SetItems,
+        Move::SlotPair,
+        Move::SimilarItem,
+        Move::FixCondition,
+    ];
+
+    /// Weight of the move in the random choice before any move is recorded.
+    fn prior(self) -> f64 {
+        match self {
+            Move::Item | Move::Points => 1.,
+            Move::SimilarItem => 0.4,
+            Move::SetItems | Move::SlotPair | Move::FixCondition => 0.2,
+        }
+    }
+}
+
+/// Chooses random moves, with probabilities adapting to their recent
+/// acceptance rate.
+struct MoveSelector {
+    weights: [f64; Move::ALL.len()],
+    rates:   [f64; Move::ALL.len()],
+    last:    Option<Move>,
+}
+
+impl MoveSelector {
+    /// Smoothing factor of the acceptance rates.
+    const RATE_SMOOTHING: f64 = 0.01;
+
+    /// Part of the probability of a move that doesn't depend on its
+    /// acceptance rate.
+    const MIN_RATE: f64 = 0.5;
+
+    fn new(pools: &SlotPools) -> Self {
+        let mut weights = [0.; Move::ALL.len()];
+
+        for (weight, kind) in weights.iter_mut().zip(&Move::ALL) {
+            let is_available = match kind {
+                Move::SetItems => !pools.sets.is_empty(),
+                Move::SlotPair => (0..pools.items.len())
+                    .any(|slot_i| pools.pair_slot(slot_i).is_some()),
+                _ => true,
+            };
+
+            if is_available {
+                *weight = kind.prior();
+            }
+        }
+
+        MoveSelector {
+            weights,
+            rates: [0.5; Move::ALL.len()],
+            last: None,
+        }
+    }
+
+    fn choose(&mut self, rng: &mut impl Rng) -> Move {
+        let weights = self
+            .weights
+            .iter()
+            .zip(&self.rates)
+            .map(|(weight, rate)| weight * (Self::MIN_RATE + rate));
+        let dist = rand::distributions::WeightedIndex::new(weights)
+            .expect("no move is available");
+        let kind = Move::ALL[dist.sample(rng)];
+        self.last = Some(kind);
+        kind
+    }
+
+    /// Update the acceptance rate of the last chosen move.
+    fn record(&mut self, accepted: bool) {
+        if let Some(kind) = self.last.take() {
+            let rate = &mut self.rates[kind as usize];
+            let accepted = if accepted { 1. } else { 0. };
+            *rate += Self::RATE_SMOOTHING * (accepted - *rate);
+        }
+    }
+}
+
+/// Items that can be equiped in each slot.
+struct SlotPools<'i> {
+    items: Vec<Vec<&'i Equipement>>,
+
+    /// Items of each set, with the slot they can be equiped in.
+    sets: BTreeMap<u64, Vec<(usize, &'i Equipement)>>,
+
+    /// Types of items accepted by each slot.
+    allowed: Vec<&'static [ItemType]>,
+}
+
+impl<'i> SlotPools<'i> {
+    /// Another slot accepting the same items as given slot, if any.
+    fn pair_slot(&self, slot_i: usize) -> Option<usize> {
+        (0..self.items.len()).find(|&other_i| {
+            other_i != slot_i
+                && !self.items[slot_i].is_empty()
+                && !self.items[other_i].is_empty()
+                && self.allowed[other_i] == self.allowed[slot_i]
+        })
+    }
+
+    /// Random slot with a non-empty pool.
+    fn random_slot(&self, rng: &mut impl Rng) -> Option<usize> {
+        (0..self.items.len())
+            .filter(|&slot_i| !self.items[slot_i].is_empty())
+            .choose(rng)
+    }
+}
+
 fn walk_character<'i>(
     init: &Character<'i>,
     rng: &mut impl rand::Rng,
-    db_slot_pool: &[Vec<&'i Equipement>],
-) -> Character<'i> {
+    pools: &SlotPools<'i>,
+    kind: Move,
+) -> Result<Character<'i>, CharacterError<'i>> {
     let mut new = init.clone();
 
-    if rng.gen_bool(0.5) {
-        // Swap some items
-        let slot_i = rng.gen_range(0, db_slot_pool.len());
-        let item = db_slot_pool[slot_i]
-            .choose(rng)
-            .expect("No available item for slot");
-        new.item_slots[slot_i].equip(item);
-        new
-    } else {
-        // Swap some statistics
-        let kind = ASSIGNABLE_CARACS.iter().choose(rng).unwrap();
-        let from = ASSIGNABLE_CARACS.iter().choose(rng).unwrap();
-
-        if new
-            .carac_spend_or_seek(kind, *[1, 5, 10].choose(rng).unwrap(), from)
-            .is_err()
-        {
-            let _ = new.carac_spend_or_seek(kind, 1, from);
+    match kind {
+        Move::Item => {
+            let slot_i =
+                pools.random_slot(rng).ok_or(CharacterError::EmptyPool)?;
+            let item = pools.items[slot_i].choose(rng).unwrap();
+            equip_replacing(&mut new, slot_i, item)?;
+        }
+        Move::Points => {
+            let kind = ASSIGNABLE_CARACS.iter().choose(rng).unwrap();
+            let from = ASSIGNABLE_CARACS.iter().choose(rng).unwrap();
+
+            if new
+                .carac_spend_or_seek(
+                    kind,
+                    *[1, 5, 10].choose(rng).unwrap(),
+                    from,
+                )
+                .is_err()
+            {
+                let _ = new.carac_spend_or_seek(kind, 1, from);
+            }
+        }
+        Move::SetItems => {
+            let set_items = pools
+                .sets
+                .values()
+                .choose(rng)
+                .ok_or(CharacterError::EmptyPool)?;
+            let mut set_items = set_items.clone();
+            set_items.shuffle(rng);
+
+            let count =
+                rng.gen_range(2, std::cmp::max(set_items.len(), 2) + 1);
+            let mut used_slots = Vec::with_capacity(count);
+            let mut used_items = Vec::with_capacity(count);
+
+            for (slot_i, item) in set_items {
+                if used_slots.len() >= count {
+                    break;
+                }
+
+                if !used_slots.contains(&slot_i)
+                    && !used_items.contains(&item._id)
+                {
+                    equip_replacing(&mut new, slot_i, item)?;
+                    used_slots.push(slot_i);
+                    used_items.push(item._id);
+                }
+            }
+        }
+        Move::SlotPair => {
+            let slot_i = (0..pools.items.len())
+                .filter(|&slot_i| pools.pair_slot(slot_i).is_some())
+                .choose(rng)
+                .ok_or(CharacterError::EmptyPool)?;
+            let other_i = (0..pools.items.len())
+                .filter(|&other_i| {
+                    other_i != slot_i
+                        && !pools.items[other_i].is_empty()
+                        && pools.allowed[other_i] == pools.allowed[slot_i]
+                })
+                .choose(rng)
+                .unwrap();
+
+            for &slot_i in &[slot_i, other_i] {
+                let item = pools.items[slot_i].choose(rng).unwrap();
+                equip_replacing(&mut new, slot_i, item)?;
+            }
+        }
+        Move::SimilarItem => {
+            let slot_i = (0..pools.items.len())
+                .filter(|&slot_i| {
+                    !pools.items[slot_i].is_empty()
+                        && new.item_slots[slot_i].get_item().is_some()
+                })
+                .choose(rng)
+                .ok_or(CharacterError::EmptySlot)?;
+            let current = new.item_slots[slot_i].get_item().unwrap();
+
+            let positive_lines = |item: &Equipement| -> Vec<CaracKind> {
+                item.statistics
+                    .iter()
+                    .filter(|(_, bounds)| *bounds.end() > 0)
+                    .map(|(kind, _)| kind)
+                    .collect()
+            };
+
+            let current_lines = positive_lines(current);
+            let similarity = |item: &Equipement| -> f64 {
+                let lines = positive_lines(item);
+                let common = lines
+                    .iter()
+                    .filter(|kind| current_lines.contains(kind))
+                    .count();
+                let union = lines.len() + current_lines.len() - common;
+                common as f64 / std::cmp::max(union, 1) as f64
+            };
+
+            let item = pools.items[slot_i]
+                .choose_multiple(rng, SIMILAR_SAMPLES)
+                .filter(|item| item._id != current._id)
+                .map(|item| (item, similarity(item)))
+                .max_by(|(_, x), (_, y)| {
+                    x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .map(|(item, _)| *item)
+                .ok_or(CharacterError::EmptyPool)?;
+
+            equip_replacing(&mut new, slot_i, item)?;
+        }
+        Move::FixCondition => {
+            let caracs = new.get_caracs();
+            let conditions = new.all_conditions();
+
+            // Atoms of violated clauses that can be fixed by moving points
+            let (kind, order, bound) = conditions
+                .clauses()
+                .iter()
+                .filter(|clause| {
+                    !clause.iter().any(|atom| match atom {
+                        ConditionAtom::Stats(kind, order, bound) => {
+                            caracs.get_carac(kind).cmp(bound) == *order
+                        }
+                        _ => true,
+                    })
+                })
+                .flatten()
+                .filter_map(|atom| match atom {
+                    ConditionAtom::Stats(kind, order, bound) => {
+                        ASSIGNABLE_CARACS
+                            .iter()
+                            .find(|assignable| *assignable == kind)
+                            .map(|kind| (kind, *order, *bound))
+                    }
+                    _ => None,
+                })
+                .choose(rng)
+                .ok_or(CharacterError::EmptyPool)?;
+
+            // Each point of characteristic costs at least one point, so
+            // larger gaps can't be closed anyway
+            let budget = points_budget(&new);
+            let current = caracs.get_carac(kind);
+            let gap = (i32::from(bound) - i32::from(current)).abs() + 1;
+            let missing = u16::try_from(gap)
+                .map_or(budget, |gap| std::cmp::min(gap, budget));
+
+            match order {
+                std::cmp::Ordering::Greater => {
+                    let from = ASSIGNABLE_CARACS
+                        .iter()
+                        .filter(|from| *from != kind)
+                        .choose(rng)
+                        .unwrap();
+                    new.carac_spend_or_seek(kind, missing, from)?;
+                }
+                std::cmp::Ordering::Less => {
+                    let base = *new.base_stats.get(kind).unwrap_or(&0);
+                    new.carac_unspend(kind, std::cmp::min(missing, base))?;
+                }
+                std::cmp::Ordering::Equal => {}
+            }
         }
-        new
     }
+
+    Ok(new)
+}
+
+/// Combine two builds: each slot gets the item of either parent, and each
+/// characteristic the points of either parent. Decreased characteristics are
+/// applied first, increases are then capped by the points left to spend.
+fn crossover_characters<'i>(
+    father: &Character<'i>,
+    mother: &Character<'i>,
+    rng: &mut impl rand::Rng,
+) -> Character<'i> {
+    let mut child = father.clone();
+
+    for slot_i in 0..child.item_slots.len() {
+        if child.item_slots[slot_i].is_locked() || rng.gen_bool(0.5) {
+            continue;
+        }
+
+        // Items that can't be combined with the rest of the build are
+        // discarded
+        let _ = match mother.item_slots[slot_i].get_item() {
+            Some(item) => equip_replacing(&mut child, slot_i, item),
+            None => child.unequip(slot_i).map(|_| ()),
+        };
+    }
+
+    let from_mother: Vec<_> = ASSIGNABLE_CARACS
+        .iter()
+        .filter(|_| rng.gen_bool(0.5))
+        .map(|kind| (kind, *mother.base_stats.get(kind).unwrap_or(&0)))
+        .collect();
+
+    for &(kind, value) in &from_mother {
+        let current = *child.base_stats.get(kind).unwrap_or(&0);
+
+        if value < current {
+            child.carac_unspend(kind, current - value).unwrap();
+        }
+    }
+
+    for &(kind, value) in &from_mother {
+        let current = *child.base_stats.get(kind).unwrap_or(&0);
+
+        if value > current {
+            // Greatest affordable increase towards the mother's value
+            let (mut min, mut max) = (0, value - current);
+
+            while min < max {
+                let mid = (min + max).div_ceil(2);
+
+                if child.carac_spend_cost(kind, mid) <= child.unspent {
+                    min = mid;
+                } else {
+                    max = mid - 1;
+                }
+            }
+
+            if min > 0 {
+                child.carac_spend(kind, min).unwrap();
+            }
+        }
+    }
+
+    child
 }
 
 pub fn eval_character(
     character: &Character<'_>,
     target: &[(RawCaracsValue, f64)],
+    constraints: &[Constraint],
 ) -> f64 {
     let target_min = |target: f64, width: f64, x: f64| -> f64 {
         1. / (1. + (-4. * (x - target) / width).exp())
@@ -82,31 +546,977 @@ pub fn eval_character(
         character.condition_overflow(&character.all_conditions()),
     );
 
-    targets_weight * conflicts_weight * conditions_weight
+    // Violated constraints are penalized proportionally to how far they are
+    // from being satisfied, to guide the search towards feasible builds
+    let constraints_weight: f64 = constraints
+        .iter()
+        .map(|constraint| {
+            let width = constraint
+                .value
+                .approx_smithmage_weight()
+                .map(|weight| 100. / weight)
+                .unwrap_or(1.);
+            let violation = constraint.violation(&caracs);
+
+            if violation > 0. {
+                0.1 * target_zero(width, violation)
+            } else {
+                1.
+            }
+        })
+        .product();
+
+    targets_weight * conflicts_weight * conditions_weight * constraints_weight
+}
+
+/// Number of points spent in assignable characteristics, plus unspent points.
+fn points_budget(character: &Character<'_>) -> u16 {
+    character.unspent
+        + ASSIGNABLE_CARACS
+            .iter()
+            .map(|kind| {
+                let value = *character.base_stats.get(kind).unwrap_or(&0);
+                Character::carac_cost_from_zero(kind, value)
+            })
+            .sum::<u16>()
 }
 
+/// Copy of a character with some assignable characteristics set to given
+/// values, points of the budget that are left are unspent.
+fn assign_points<'i>(
+    character: &Character<'i>,
+    budget: u16,
+    values: impl IntoIterator<Item = (&'static CaracKind, u16)>,
+) -> Character<'i> {
+    let mut new = character.clone();
+
+    for (kind, value) in values {
+        if value == 0 {
+            new.base_stats.remove(kind);
+        } else {
+            new.base_stats.insert(kind, value);
+        }
+    }
+
+    new.unspent = budget.saturating_sub(points_budget(&new) - new.unspent);
+    new
+}
+
+/// Spread the characteristic points of a character to maximize
+/// `eval_character`, its items are left unchanged.
+///
+/// Each characteristic is given values that are multiples of `resolution`,
+/// and the gain of each value is evaluated while other characteristics are
+/// left unchanged. Points are then spread by dynamic programming over the
+/// number of points spent in each characteristic, which is exact when the
+/// logarithm of the evaluation is a sum of terms that only depend on a
+/// single characteristic. Otherwise, this is repeated from the new
+/// allocation as long as it improves the evaluation.
+///
+/// The output is never evaluated lower than the input.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// use dofus_stuff::character::{Character, RawCaracsValue};
+/// use dofus_stuff::dofapi::{CaracKind::*, Element::*};
+/// use dofus_stuff::search::{allocate_points, eval_character};
+///
+/// let db_sets = HashMap::new();
+/// let mut character = Character::new(200, &db_sets);
+/// character.carac_spend(&Wisdom, 331).unwrap();
+///
+/// let target = [
+///     (RawCaracsValue::Carac(Vitality), 500.),
+///     (RawCaracsValue::Carac(Stats(Air)), 200.),
+/// ];
+/// let allocated = allocate_points(&character, &target, &[], 1);
+/// let caracs = allocated.get_caracs();
+///
+/// assert!(caracs.eval(&target[0].0) >= 500.);
+/// assert!(caracs.eval(&target[1].0) >= 200.);
+/// assert!(
+///     eval_character(&allocated, &target, &[])
+///         > eval_character(&character, &target, &[])
+/// );
+/// ```
+pub fn allocate_points<'i>(
+    character: &Character<'i>,
+    target: &[(RawCaracsValue, f64)],
+    constraints: &[Constraint],
+    resolution: u16,
+) -> Character<'i> {
+    const MAX_ROUNDS: usize = 4;
+
+    let resolution = std::cmp::max(resolution, 1);
+    let budget = points_budget(character);
+    let log_eval = |character: &Character<'i>| {
+        eval_character(character, target, constraints)
+            .max(f64::MIN_POSITIVE)
+            .ln()
+    };
+
+    let mut best = character.clone();
+    let mut best_eval = log_eval(&best);
+
+    for _ in 0..MAX_ROUNDS {
+        // best_gains[b]: best total gain of the characteristics handled so
+        // far with at most b points
+        let mut best_gains = vec![0.; usize::from(budget) + 1];
+        let mut choices = Vec::with_capacity(ASSIGNABLE_CARACS.len());
+
+        for kind in ASSIGNABLE_CARACS {
+            let cost = |value| Character::carac_cost_from_zero(kind, value);
+            let current = *best.base_stats.get(kind).unwrap_or(&0);
+
+            let mut values: Vec<u16> = (0..)
+                .step_by(usize::from(resolution))
+                .take_while(|&value| cost(value) <= budget)
+                .collect();
+            let mut max_value = *values.last().unwrap();
+
+            while cost(max_value + 1) <= budget {
+                max_value += 1;
+            }
+
+            values.push(max_value);
+            values.push(current);
+            values.sort_unstable();
+            values.dedup();
+
+            let gains: Vec<_> = values
+                .into_iter()
+                .map(|value| {
+                    let new =
+                        assign_points(&best, budget, Some((kind, value)));
+                    (value, usize::from(cost(value)), log_eval(&new))
+                })
+                .collect();
+
+            let mut next_gains = vec![f64::NEG_INFINITY; best_gains.len()];
+            let mut choice = vec![0; best_gains.len()];
+
+            for (points, next_gain) in next_gains.iter_mut().enumerate() {
+                for &(value, cost, gain) in &gains {
+                    if cost > points {
+                        break;
+                    }
+
+                    let total = best_gains[points - cost] + gain;
+
+                    if total > *next_gain {
+                        *next_gain = total;
+                        choice[points] = value;
+                    }
+                }
+            }
+
+            best_gains = next_gains;
+            choices.push(choice);
+        }
+
+        let mut points = budget;
+        let mut values = Vec::with_capacity(ASSIGNABLE_CARACS.len());
+
+        for (kind, choice) in ASSIGNABLE_CARACS.iter().zip(&choices).rev() {
+            let value = choice[usize::from(points)];
+            points -= Character::carac_cost_from_zero(kind, value);
+            values.push((kind, value));
+        }
+
+        let new = assign_points(&best, budget, values);
+        let new_eval = log_eval(&new);
+
+        if new_eval <= best_eval {
+            break;
+        }
+
+        best = new;
+        best_eval = new_eval;
+    }
+
+    best
+}
+
+/// Search for a build maximizing `eval_character` with given optimizer, runs
+/// with the same seed always output the same build unless they are stopped by
+/// a time limit.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// use dofus_stuff::character::{Character, RawCaracsValue};
+/// use dofus_stuff::dofapi::{CaracKind, Equipement};
+/// use dofus_stuff::search::{optimize_character, OptimizerConfig};
+///
+/// let db: Vec<Equipement> = serde_json::from_str(
+///     r#"[{
+///         "type": "Hat", "ankamaId": 1, "_id": 1, "name": "Hat",
+///         "level": 100, "url": "", "imgUrl": "",
+///         "statistics": [{"Vitality": {"min": 1, "max": 100}}]
+///     }]"#,
+/// )
+/// .unwrap();
+///
+/// let db_sets = HashMap::new();
+/// let target = [(RawCaracsValue::Carac(CaracKind::Vitality), 2000.)];
+///
+/// let character = optimize_character(
+///     Character::new(100, &db_sets),
+///     &target,
+///     &[],
+///     &db,
+///     &OptimizerConfig::default(),
+///     42,
+///     &|_| true,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(character.item_slots[0].get_item(), Some(&db[0]));
+/// ```
 pub fn optimize_character<'i>(
     init: Character<'i>,
     target: &[(RawCaracsValue, f64)],
+    constraints: &[Constraint],
     db_equipements: &'i [Equipement],
-) -> Character<'i> {
-    // Reorder set into pools assigned to each slot
+    optimizer: &impl Optimizer,
+    seed: u64,
+    observer: &Observer<'_, 'i>,
+) -> Result<Character<'i>, CharacterError<'i>> {
+    let slot_pool = build_slot_pools(&init, db_equipements)?;
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut archive = Archive::new(1, 1);
+    let chain = Chain {
+        index: 0,
+        target,
+        constraints,
+        slot_pool: &slot_pool,
+        optimizer,
+        observer,
+        allocation: Allocation::default(),
+    };
+
+    chain.run(init, &mut rng, &mut archive)?;
+
+    let builds = refine_builds(archive.into_builds(), target, constraints);
+    let (character, _) = builds.into_iter().next().unwrap();
+    Ok(character)
+}
+
+/// Parameters of a search made of several independent chains.
+#[derive(Clone, Debug)]
+pub struct SearchConfig {
+    pub optimizer: OptimizerConfig,
+
+    /// Number of worker threads running chains.
+    pub threads: usize,
+
+    /// Number of independent chains.
+    pub restarts: usize,
+
+    pub seed: u64,
+
+    /// Number of builds to output, at least one build is output.
+    pub top: usize,
+
+    /// Minimal number of different items between two output builds.
+    pub min_distance: usize,
+
+    /// Optimal allocations of characteristic points proposed during the
+    /// search.
+    pub allocation: Allocation,
+}
+
+/// Periodic proposal of an optimal allocation of characteristic points to
+/// the current build of an optimizer.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default)]
+pub struct Allocation {
+    /// Number of steps of the optimizer between two proposals, 0 disables
+    /// them.
+    pub period: u32,
+
+    /// Points are spread by multiples of this value, see `allocate_points`.
+    pub resolution: u16,
+}
+
+impl Default for Allocation {
+    fn default() -> Self {
+        Allocation {
+            period:     5_000,
+            resolution: 10,
+        }
+    }
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            optimizer:    OptimizerConfig::default(),
+            threads:      1,
+            restarts:     1,
+            seed:         0,
+            top:          1,
+            min_distance: 1,
+            allocation:   Allocation::default(),
+        }
+    }
+}
+
+/// Run `config.restarts` independent chains spread over `config.threads`
+/// worker threads and return the best distinct builds found by all of them,
+/// together with their evaluation. Each chain is seeded from `config.seed`
+/// and its index, so the output doesn't depend on the number of threads.
+pub fn optimize_character_parallel<'i>(
+    init: Character<'i>,
+    target: &[(RawCaracsValue, f64)],
+    constraints: &[Constraint],
+    db_equipements: &'i [Equipement],
+    config: &SearchConfig,
+    observer: &Observer<'_, 'i>,
+) -> Result<Vec<(Character<'i>, f64)>, CharacterError<'i>> {
+    let slot_pool = build_slot_pools(&init, db_equipements)?;
+    let restarts = std::cmp::max(config.restarts, 1);
+    let threads = std::cmp::min(std::cmp::max(config.threads, 1), restarts);
+    let top = std::cmp::max(config.top, 1);
+    let next_chain = AtomicUsize::new(0);
+
+    let mut results: Vec<_> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+
+                    loop {
+                        let chain = next_chain.fetch_add(1, Ordering::Relaxed);
+
+                        if chain >= restarts {
+                            break;
+                        }
+
+                        let mut rng = ChaCha8Rng::seed_from_u64(
+                            config.seed.wrapping_add(chain as u64),
+                        );
+                        let mut archive =
+                            Archive::new(top, config.min_distance);
+                        let result = Chain {
+                            index: chain,
+                            target,
+                            constraints,
+                            slot_pool: &slot_pool,
+                            optimizer: &config.optimizer,
+                            observer,
+                            allocation: config.allocation,
+                        }
+                        .run(
+                            init.clone(),
+                            &mut rng,
+                            &mut archive,
+                        );
+                        results.push((chain, result.map(|()| archive)));
+                    }
+
+                    results
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("search thread panicked"))
+            .collect()
+    });
+
+    // Results are merged in a deterministic order
+    results.sort_by_key(|(chain, _)| *chain);
+
+    let mut archive = Archive::new(top, config.min_distance);
+    let mut first_err = None;
+
+    for (_, result) in results {
+        match result {
+            Ok(chain_archive) => {
+                for (character, eval) in chain_archive.into_builds() {
+                    archive.insert(&character, eval);
+                }
+            }
+            Err(err) => {
+                first_err.get_or_insert(err);
+            }
+        }
+    }
+
+    match first_err {
+        Some(err) if archive.is_empty() => Err(err),
+        _ => Ok(refine_builds(archive.into_builds(), target, constraints)),
+    }
+}
+
+/// Spread the points of output builds optimally as long as they still
+/// satisfy hard constraints, builds are then sorted again.
+fn refine_builds<'i>(
+    builds: Vec<(Character<'i>, f64)>,
+    target: &[(RawCaracsValue, f64)],
+    constraints: &[Constraint],
+) -> Vec<(Character<'i>, f64)> {
+    let mut refined: Vec<_> = builds
+        .into_iter()
+        .map(|(character, eval)| {
+            let allocated =
+                allocate_points(&character, target, constraints, 1);
+            let allocated_eval =
+                eval_character(&allocated, target, constraints);
+            let caracs = allocated.get_caracs();
+
+            if allocated_eval > eval
+                && constraints.iter().all(|c| c.is_satisfied(&caracs))
+            {
+                (allocated, allocated_eval)
+            } else {
+                (character, eval)
+            }
+        })
+        .collect();
+
+    refined.sort_by(|(_, x), (_, y)| {
+        y.partial_cmp(x).unwrap_or(std::cmp::Ordering::Equal)
+    });
+    refined
+}
+
+/// Best builds met during a search, such that any two of them differ by at
+/// least `min_distance` items.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// use dofus_stuff::character::Character;
+/// # use dofus_stuff::dofapi::Equipement;
+/// use dofus_stuff::search::Archive;
+/// # let parse_item = |item_type, id| -> Equipement {
+/// #     serde_json::from_str(&format!(
+/// #         r#"{{
+/// #             "type": "{}", "ankamaId": {}, "_id": {}, "name": "Item",
+/// #             "level": 1, "url": "", "imgUrl": ""
+/// #         }}"#,
+/// #         item_type, id, id
+/// #     ))
+/// #     .unwrap()
+/// # };
+///
+/// let hats = [parse_item("Hat", 1), parse_item("Hat", 2)];
+/// let cloak = parse_item("Cloak", 3);
+///
+/// let db_sets = HashMap::new();
+/// let build = |hat| {
+///     let mut character = Character::new(200, &db_sets);
+///     character.equip(0, &hats[hat]).unwrap();
+///     character.equip(1, &cloak).unwrap();
+///     character
+/// };
+///
+/// // Only the best of two identical builds is kept, while builds differing
+/// // by one item are both kept
+/// let mut archive = Archive::new(2, 1);
+/// assert!(archive.insert(&build(0), 0.5));
+/// assert!(!archive.insert(&build(0), 0.4));
+/// assert!(archive.insert(&build(0), 0.6));
+/// assert!(archive.insert(&build(1), 0.1));
+///
+/// let evals: Vec<_> = archive
+///     .into_builds()
+///     .into_iter()
+///     .map(|(_, eval)| eval)
+///     .collect();
+/// assert_eq!(evals, [0.6, 0.1]);
+/// ```
+pub struct Archive<'i> {
+    size:         usize,
+    min_distance: usize,
+    builds:       Vec<(Character<'i>, f64)>,
+}
+
+impl<'i> Archive<'i> {
+    pub fn new(size: usize, min_distance: usize) -> Self {
+        Archive {
+            size,
+            min_distance: std::cmp::max(min_distance, 1),
+            builds: Vec::with_capacity(size),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.builds.is_empty()
+    }
+
+    /// Check if a build with given evaluation could be inserted.
+    pub fn may_accept(&self, eval: f64) -> bool {
+        match self.builds.last() {
+            _ if self.size == 0 => false,
+            Some((_, worst)) if self.builds.len() >= self.size => {
+                eval > *worst
+            }
+            _ => true,
+        }
+    }
+
+    /// Insert a build if it is better than all the builds of the archive
+    /// that are too close to it, which are then removed.
+    pub fn insert(&mut self, character: &Character<'i>, eval: f64) -> bool {
+        if !self.may_accept(eval) {
+            return false;
+        }
+
+        let min_distance = self.min_distance;
+        let is_close = |other: &Character| {
+            character.count_different_items(other) < min_distance
+        };
+
+        if self
+            .builds
+            .iter()
+            .any(|(other, other_eval)| is_close(other) && *other_eval >= eval)
+        {
+            return false;
+        }
+
+        self.builds.retain(|(other, _)| !is_close(other));
+        let pos = self
+            .builds
+            .iter()
+            .position(|(_, other_eval)| *other_eval < eval)
+            .unwrap_or(self.builds.len());
+        self.builds.insert(pos, (character.clone(), eval));
+        self.builds.truncate(self.size);
+        true
+    }
+
+    /// Best build of the archive with its evaluation.
+    pub fn best(&self) -> Option<&(Character<'i>, f64)> {
+        self.builds.first()
+    }
+
+    /// List of builds, sorted by decreasing evaluation.
+    pub fn into_builds(self) -> Vec<(Character<'i>, f64)> {
+        self.builds
+    }
+}
+
+/// Items remaining after removing dominated items.
+#[derive(Debug)]
+pub struct Pruning<'i> {
+    pub items: Vec<&'i Equipement>,
+
+    /// Size of the pool of items of each slot before and after pruning,
+    /// locked slots have empty pools.
+    pub pools: Vec<(usize, usize)>,
+}
+
+/// Remove items that are dominated by other items of the same type for the
+/// characteristics that matter to the targets, the hard constraints and the
+/// conditions of all items. An item dominates another one if it has the same
+/// set, a lower level, weaker conditions and better characteristics, items
+/// are only removed if they are dominated by as many items as there are slots
+/// they can be equiped in.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// use dofus_stuff::character::{Character, RawCaracsValue};
+/// use dofus_stuff::dofapi::{CaracKind, Equipement};
+/// use dofus_stuff::search::prune_dominated;
+/// # let parse_item = |item_type, id, vitality, wisdom| -> Equipement {
+/// #     serde_json::from_str(&format!(
+/// #         r#"{{
+/// #             "type": "{}", "ankamaId": {}, "_id": {}, "name": "Item",
+/// #             "level": 100, "url": "", "imgUrl": "",
+/// #             "statistics": [
+/// #                 {{"Vitality": {{"min": {}, "max": {}}}}},
+/// #                 {{"Wisdom": {{"min": {}, "max": {}}}}}
+/// #             ]
+/// #         }}"#,
+/// #         item_type, id, id, vitality, vitality, wisdom, wisdom
+/// #     ))
+/// #     .unwrap()
+/// # };
+///
+/// // Items giving vitality and wisdom
+/// let db = vec![
+///     parse_item("Hat", 1, 200, 0),
+///     parse_item("Hat", 2, 150, 50),
+///     parse_item("Ring", 3, 100, 0),
+///     parse_item("Ring", 4, 80, 0),
+///     parse_item("Ring", 5, 60, 0),
+/// ];
+///
+/// let db_sets = HashMap::new();
+/// let init = Character::new(200, &db_sets);
+/// let target = [(RawCaracsValue::Carac(CaracKind::Vitality), 3000.)];
+/// let pruning = prune_dominated(&init, &target, &[], &db);
+///
+/// // Wisdom doesn't matter, and only the two best rings are useful
+/// let ids: Vec<_> = pruning.items.iter().map(|item| item._id).collect();
+/// assert_eq!(ids, [1, 3, 4]);
+/// assert_eq!(pruning.pools[0], (2, 1));
+/// assert_eq!(pruning.pools[3], (3, 2));
+/// ```
+pub fn prune_dominated<'i>(
+    init: &Character<'i>,
+    target: &[(RawCaracsValue, f64)],
+    constraints: &[Constraint],
+    db_equipements: &'i [Equipement],
+) -> Pruning<'i> {
+    let items: Vec<_> = db_equipements
+        .iter()
+        .filter(|item| item.level <= init.get_level())
+        .collect();
+
+    // Targets without a clear direction prevent pruning on their
+    // characteristics
+    let target_influences = target.iter().flat_map(|(value, _)| {
+        let direction = value.direction();
+        value
+            .influences()
+            .into_iter()
+            .map(move |(kind, influence)| match direction {
+                Influence::Increasing => (kind, influence),
+                Influence::Decreasing => (kind, influence.reverse()),
+                Influence::Any => (kind, Influence::Any),
+            })
+    });
+
+    let constraint_influences = constraints.iter().flat_map(|constraint| {
+        let op = constraint.op;
+        constraint.value.influences().into_iter().map(
+            move |(kind, influence)| match op {
+                Operator::AtLeast => (kind, influence),
+                Operator::AtMost => (kind, influence.reverse()),
+                Operator::Equal => (kind, Influence::Any),
+            },
+        )
+    });
+
+    // Conditions of items may be broken by other items
+    let condition_influences = items
+        .iter()
+        .flat_map(|item| item.conditions.clauses())
+        .flatten()
+        .filter_map(|atom| match atom {
+            ConditionAtom::Stats(kind, ord, _) => {
+                let influence = match ord {
+                    std::cmp::Ordering::Greater => Influence::Increasing,
+                    std::cmp::Ordering::Less => Influence::Decreasing,
+                    std::cmp::Ordering::Equal => Influence::Any,
+                };
+                Some((kind, influence))
+            }
+            _ => None,
+        })
+        .flat_map(|(kind, influence)| {
+            RawCaracs::carac_sources(kind)
+                .into_iter()
+                .map(move |kind| (kind, influence))
+        });
+
+    let influences = merge_influences(
+        target_influences
+            .chain(constraint_influences)
+            .chain(condition_influences),
+    );
+
+    let dominates = |(i, item): (usize, &Equipement),
+                     (j, other): (usize, &Equipement)|
+     -> bool {
+        let is_better = |item: &Equipement, other: &Equipement| {
+            item.item_type == other.item_type
+                && item.level <= other.level
+                && item.set_id == other.set_id
+                && item.conditions.is_implied_by(&other.conditions)
+                && item.statistics.dominates(&other.statistics, &influences)
+        };
+
+        // Only one of two equivalent items is kept
+        i != j && is_better(item, other) && (i < j || !is_better(other, item))
+    };
+
+    let pruned_items: Vec<_> = items
+        .iter()
+        .enumerate()
+        .filter(|&(j, other)| {
+            let slots = init
+                .item_slots
+                .iter()
+                .filter(|slot| {
+                    !slot.is_locked()
+                        && slot.get_allowed().contains(&other.item_type)
+                })
+                .count();
+
+            items
+                .iter()
+                .enumerate()
+                .filter(|&(i, item)| dominates((i, item), (j, other)))
+                .take(slots)
+                .count()
+                < slots
+        })
+        .map(|(_, item)| *item)
+        .collect();
+
+    let pool_size = |slot_i: usize, items: &[&Equipement]| {
+        let slot = &init.item_slots[slot_i];
+
+        if slot.is_locked() {
+            0
+        } else {
+            items
+                .iter()
+                .filter(|item| slot.get_allowed().contains(&item.item_type))
+                .count()
+        }
+    };
+
+    let pools = (0..init.item_slots.len())
+        .map(|slot_i| {
+            (pool_size(slot_i, &items), pool_size(slot_i, &pruned_items))
+        })
+        .collect();
+
+    Pruning {
+        items: pruned_items,
+        pools,
+    }
+}
+
+/// Reorder items into pools assigned to each slot, locked slots are left with
+/// an empty pool.
+fn build_slot_pools<'i>(
+    init: &Character<'i>,
+    db_equipements: &'i [Equipement],
+) -> Result<SlotPools<'i>, CharacterError<'i>> {
     let slot_pool: Vec<_> = init
         .item_slots
         .iter()
         .map(|slot| {
             db_equipements
                 .iter()
+                .filter(|_| !slot.is_locked())
                 .filter(|item| slot.get_allowed().contains(&item.item_type))
+                .filter(|item| item.level <= init.get_level())
                 .collect::<Vec<_>>()
         })
         .collect();
 
-    rls(
-        init,
-        STEPS,
-        &mut rand::thread_rng(),
-        |character| eval_character(character, target),
-        |character, rng| walk_character(&character, rng, &slot_pool),
-    )
+    let all_locked = init.item_slots.iter().all(|slot| slot.is_locked());
+
+    if !all_locked && slot_pool.iter().all(Vec::is_empty) {
+        return Err(CharacterError::EmptyPool);
+    }
+
+    let mut sets = BTreeMap::new();
+
+    for (slot_i, pool) in slot_pool.iter().enumerate() {
+        for item in pool {
+            if let Some(set_id) = item.set_id {
+                sets.entry(set_id)
+                    .or_insert_with(Vec::new)
+                    .push((slot_i, *item));
+            }
+        }
+    }
+
+    Ok(SlotPools {
+        items: slot_pool,
+        sets,
+        allowed: init
+            .item_slots
+            .iter()
+            .map(|slot| slot.get_allowed())
+            .collect(),
+    })
+}
+
+/// Callback receiving the state of the search after each step of each chain,
+/// the chain stops early if it returns `false`.
+pub type Observer<'o, 'i> =
+    dyn Fn(&SearchProgress<'_, 'i>) -> bool + Sync + 'o;
+
+/// State of a chain of a search after a step.
+pub struct SearchProgress<'a, 'i> {
+    /// Index of the chain.
+    pub chain: usize,
+
+    pub progress: Progress,
+
+    /// Best build of the chain satisfying all hard constraints, if it was
+    /// found during this step.
+    pub improved: Option<&'a (Character<'i>, f64)>,
+}
+
+/// Parameters of a single chain.
+struct Chain<'c, 'i, O> {
+    index:       usize,
+    target:      &'c [(RawCaracsValue, f64)],
+    constraints: &'c [Constraint],
+    slot_pool:   &'c SlotPools<'i>,
+    optimizer:   &'c O,
+    observer:    &'c Observer<'c, 'i>,
+    allocation:  Allocation,
+}
+
+/// Builds explored by a chain, builds satisfying all hard constraints are
+/// recorded into the archive when they are evaluated.
+struct CharacterSpace<'c, 'i> {
+    init:        Character<'i>,
+    target:      &'c [(RawCaracsValue, f64)],
+    constraints: &'c [Constraint],
+    slot_pool:   &'c SlotPools<'i>,
+    archive:     RefCell<&'c mut Archive<'i>>,
+    moves:       RefCell<MoveSelector>,
+    allocation:  Allocation,
+}
+
+impl<'i> SearchSpace for CharacterSpace<'_, 'i> {
+    type Point = Character<'i>;
+
+    fn init(&self) -> Character<'i> {
+        self.init.clone()
+    }
+
+    fn eval(&self, character: &Character<'i>) -> f64 {
+        let eval = eval_character(character, self.target, self.constraints);
+        let mut archive = self.archive.borrow_mut();
+
+        if archive.may_accept(eval) {
+            let caracs = character.get_caracs();
+
+            if self.constraints.iter().all(|c| c.is_satisfied(&caracs)) {
+                archive.insert(character, eval);
+            }
+        }
+
+        eval
+    }
+
+    fn walk<R: Rng>(
+        &self,
+        character: Character<'i>,
+        rng: &mut R,
+    ) -> Character<'i> {
+        let mut moves = self.moves.borrow_mut();
+        let kind = moves.choose(rng);
+
+        match walk_character(&character, rng, self.slot_pool, kind) {
+            Ok(new) => new,
+            Err(_) => {
+                // Moves that would break the character or can't be applied to
+                // it are discarded, and count as rejected
+                moves.record(false);
+                character
+            }
+        }
+    }
+
+    fn record_move(&self, accepted: bool) {
+        self.moves.borrow_mut().record(accepted);
+    }
+
+    fn refine(
+        &self,
+        character: &Character<'i>,
+        step: u32,
+    ) -> Option<Self::Point> {
+        let period = self.allocation.period;
+
+        if period == 0 || !step.is_multiple_of(period) {
+            return None;
+        }
+
+        Some(allocate_points(
+            character,
+            self.target,
+            self.constraints,
+            self.allocation.resolution,
+        ))
+    }
+
+    fn crossover<R: Rng>(
+        &self,
+        father: &Character<'i>,
+        mother: &Character<'i>,
+        rng: &mut R,
+    ) -> Character<'i> {
+        crossover_characters(father, mother, rng)
+    }
+
+    fn key(&self, character: &Character<'i>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        for slot in &character.item_slots {
+            slot.get_item().map(|item| item._id).hash(&mut hasher);
+        }
+
+        for kind in ASSIGNABLE_CARACS {
+            character.base_stats.get(kind).hash(&mut hasher);
+        }
+
+        hasher.finish()
+    }
+}
+
+impl<'i, O: Optimizer> Chain<'_, 'i, O> {
+    /// Run the chain from `init`, recording builds satisfying all hard
+    /// constraints into `archive`.
+    fn run(
+        &self,
+        init: Character<'i>,
+        rng: &mut impl Rng,
+        archive: &mut Archive<'i>,
+    ) -> Result<(), CharacterError<'i>> {
+        let last_best = Cell::new(f64::NEG_INFINITY);
+        let space = CharacterSpace {
+            init,
+            target: self.target,
+            constraints: self.constraints,
+            slot_pool: self.slot_pool,
+            archive: RefCell::new(archive),
+            moves: RefCell::new(MoveSelector::new(self.slot_pool)),
+            allocation: self.allocation,
+        };
+
+        let result = self.optimizer.optimize(&space, rng, |progress| {
+            let archive = space.archive.borrow();
+            let improved =
+                archive.best().filter(|(_, eval)| *eval > last_best.get());
+
+            if let Some((_, eval)) = improved {
+                last_best.set(*eval);
+            }
+
+            (self.observer)(&SearchProgress {
+                chain: self.index,
+                progress: *progress,
+                improved,
+            })
+        });
+
+        if !space.archive.into_inner().is_empty() {
+            return Ok(());
+        }
+
+        let caracs = result.get_caracs();
+        let violated = self
+            .constraints
+            .iter()
+            .enumerate()
+            .filter(|(_, constraint)| !constraint.is_satisfied(&caracs))
+            .map(|(index, _)| index)
+            .collect();
+
+        Err(CharacterError::Infeasible(violated))
+    }
 }
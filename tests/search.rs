@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use dofus_stuff::character::{Character, RawCaracsValue};
+use dofus_stuff::dofapi::{CaracKind, Equipement};
+use dofus_stuff::search::{
+    optimize_character, optimize_character_parallel, AnnealingConfig,
+    GeneticConfig, OptimizerConfig, SearchConfig, TabuConfig,
+};
+
+/// Hats giving increasing vitality.
+fn vitality_hats() -> Vec<Equipement> {
+    (1..=20)
+        .map(|id| {
+            serde_json::from_str(&format!(
+                r#"{{
+                    "type": "Hat", "ankamaId": {}, "_id": {}, "name": "Hat",
+                    "level": 100, "url": "", "imgUrl": "",
+                    "statistics": [{{"Vitality": {{"min": 1, "max": {}}}}}]
+                }}"#,
+                id,
+                id,
+                10 * id
+            ))
+            .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn runs_with_the_same_seed_output_the_same_build() {
+    let db = vitality_hats();
+    let db_sets = HashMap::new();
+    let target = [(RawCaracsValue::Carac(CaracKind::Vitality), 2000.)];
+
+    let run = |optimizer: &OptimizerConfig, seed| {
+        let character = optimize_character(
+            Character::new(100, &db_sets),
+            &target,
+            &[],
+            &db,
+            optimizer,
+            seed,
+            &|_| true,
+        )
+        .unwrap();
+        (
+            character.iter_items().collect::<Vec<_>>(),
+            character.base_stats,
+        )
+    };
+
+    let optimizers = [
+        OptimizerConfig::Annealing(AnnealingConfig {
+            steps: 10_000,
+            ..Default::default()
+        }),
+        OptimizerConfig::Genetic(GeneticConfig {
+            generations: 200,
+            ..Default::default()
+        }),
+        OptimizerConfig::Tabu(TabuConfig {
+            steps: 1_000,
+            ..Default::default()
+        }),
+    ];
+
+    for optimizer in &optimizers {
+        assert_eq!(run(optimizer, 42), run(optimizer, 42));
+    }
+}
+
+#[test]
+fn parallel_runs_do_not_depend_on_the_number_of_threads() {
+    let db = vitality_hats();
+    let db_sets = HashMap::new();
+    let target = [(RawCaracsValue::Carac(CaracKind::Vitality), 2000.)];
+
+    let run = |threads| {
+        let config = SearchConfig {
+            optimizer: OptimizerConfig::Annealing(AnnealingConfig {
+                steps: 2_000,
+                ..Default::default()
+            }),
+            threads,
+            restarts: 8,
+            seed: 42,
+            top: 3,
+            ..Default::default()
+        };
+
+        optimize_character_parallel(
+            Character::new(100, &db_sets),
+            &target,
+            &[],
+            &db,
+            &config,
+            &|_| true,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|(character, eval)| {
+            (character.iter_items().collect::<Vec<_>>(), eval)
+        })
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(run(1), run(4));
+}
+
+#[test]
+fn unreachable_conditions_do_not_crash_the_search() {
+    let hat: Equipement = serde_json::from_str(
+        r#"{
+            "type": "Hat", "ankamaId": 1, "_id": 1, "name": "Hat",
+            "level": 100, "url": "", "imgUrl": "",
+            "statistics": [{"Vitality": {"min": -30000, "max": -30000}}],
+            "conditions": ["Vitality > 30000"]
+        }"#,
+    )
+    .unwrap();
+    let db = vec![hat];
+    let db_sets = HashMap::new();
+    let target = [(RawCaracsValue::Carac(CaracKind::Vitality), 2000.)];
+
+    let optimizer = OptimizerConfig::Annealing(AnnealingConfig {
+        steps: 1_000,
+        ..Default::default()
+    });
+
+    for seed in 0..10 {
+        let mut init = Character::new(100, &db_sets);
+        init.equip(0, &db[0]).unwrap();
+
+        optimize_character(init, &target, &[], &db, &optimizer, seed, &|_| {
+            true
+        })
+        .unwrap();
+    }
+}